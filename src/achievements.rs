@@ -0,0 +1,235 @@
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+use crate::external::ext_badge_minter;
+use crate::structs::achievement_json::UserAchievementJson;
+use crate::utils::{get_now_ms, get_today_day};
+use crate::roles::Role;
+use crate::{Day, MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+pub type AchievementId = String;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde", tag = "kind", rename_all = "snake_case")]
+pub enum AchievementCondition {
+    Wins { count: u32 },
+    // Days in a row with at least one win
+    WinStreak { days: u32 },
+    FastWin { max_duration_ms: u64 },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Achievement {
+    pub achievement_id: AchievementId,
+    pub title: String,
+    pub condition: AchievementCondition,
+}
+
+pub fn default_achievements() -> Vec<Achievement> {
+    vec![
+        Achievement {
+            achievement_id: "first_win".to_string(),
+            title: "First win".to_string(),
+            condition: AchievementCondition::Wins { count: 1 },
+        },
+        Achievement {
+            achievement_id: "ten_wins".to_string(),
+            title: "10 wins".to_string(),
+            condition: AchievementCondition::Wins { count: 10 },
+        },
+        Achievement {
+            achievement_id: "seven_day_streak".to_string(),
+            title: "7-day streak".to_string(),
+            condition: AchievementCondition::WinStreak { days: 7 },
+        },
+        Achievement {
+            achievement_id: "under_30_seconds".to_string(),
+            title: "Solved in under 30 seconds".to_string(),
+            condition: AchievementCondition::FastWin { max_duration_ms: 30_000 },
+        },
+    ]
+}
+
+/// Where unlocked achievements are minted as soulbound NEP-171 tokens. The player attaches the deposit,
+/// which pays the token storage
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeConfig {
+    #[schemars(with = "String")]
+    pub badge_contract: AccountId,
+    #[schemars(with = "String")]
+    pub mint_deposit: U128,
+}
+
+impl BadgeConfig {
+    pub fn assert_valid(&self) {
+        require!(self.mint_deposit.0 > 0, "Mint deposit must be greater than 0");
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct PlayerStats {
+    pub wins: u32,
+    pub last_win_day: Day,
+    pub streak_days: u32,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct UnlockedAchievement {
+    pub achievement_id: AchievementId,
+    pub unlocked_at: u64,
+    // Set when the badge is claimed, cleared again if the mint fails
+    pub badge_minted: bool,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_achievements(&self) -> Vec<Achievement> {
+        self.achievements.values().collect()
+    }
+
+    /// Adds the achievement, or replaces the one with the same id. Accounts that already unlocked it keep it
+    pub fn set_achievement(&mut self, achievement: Achievement) {
+        self.assert_role(Role::Admin);
+        require!(!achievement.achievement_id.is_empty(), "Achievement id can't be empty");
+        self.achievements.insert(&achievement.achievement_id.clone(), &achievement);
+    }
+
+    pub fn remove_achievement(&mut self, achievement_id: AchievementId) {
+        self.assert_role(Role::Admin);
+        require!(self.achievements.remove(&achievement_id).is_some(), "Achievement not found");
+    }
+
+    pub fn get_user_achievements(&self, account_id: AccountId) -> Vec<UserAchievementJson> {
+        self.user_achievements.get(&account_id).unwrap_or_default().iter()
+            .map(UserAchievementJson::from)
+            .collect()
+    }
+
+    pub fn get_badge_config(&self) -> Option<BadgeConfig> {
+        self.badge_config.clone()
+    }
+
+    /// Without a badge config achievements are only kept in this contract
+    pub fn set_badge_config(&mut self, badge_config: Option<BadgeConfig>) {
+        self.assert_role(Role::Admin);
+        if let Some(badge_config) = &badge_config {
+            badge_config.assert_valid();
+        }
+        self.badge_config = badge_config;
+    }
+
+    /// Mints the badge of an achievement the caller unlocked. The caller pays the mint deposit, anything
+    /// attached above it is refunded
+    #[payable]
+    pub fn claim_badge(&mut self, achievement_id: AchievementId) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        self.assert_settlement_not_paused();
+        let badge_config = self.badge_config.clone().expect("Badges are not enabled");
+        let deposit = env::attached_deposit().as_yoctonear();
+        require!(deposit >= badge_config.mint_deposit.0, format!("Deposit must be at least {} yoctoNEAR", badge_config.mint_deposit.0));
+        let achievement = self.achievements.get(&achievement_id).expect("Achievement not found");
+
+        let mut unlocked = self.user_achievements.get(&account_id).unwrap_or_default();
+        let unlocked_achievement = unlocked.iter_mut()
+            .find(|unlocked_achievement| unlocked_achievement.achievement_id == achievement_id)
+            .expect("Achievement not unlocked by the user");
+        require!(!unlocked_achievement.badge_minted, "Badge already minted");
+        unlocked_achievement.badge_minted = true;
+        self.user_achievements.insert(&account_id, &unlocked);
+
+        if deposit > badge_config.mint_deposit.0 {
+            Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(deposit - badge_config.mint_deposit.0));
+        }
+        self.mint_badge(&account_id, &achievement, &badge_config)
+    }
+
+    // A failed mint gives the deposit back and lets the player claim the badge again
+    #[private]
+    pub fn on_badge_mint(&mut self, account_id: AccountId, achievement_id: AchievementId, deposit: U128) -> bool {
+        match env::promise_result(0) {
+            near_sdk::PromiseResult::Successful(_) => true,
+            near_sdk::PromiseResult::Failed => {
+                log!("Badge {} of {} failed to mint", achievement_id, account_id);
+                let mut unlocked = self.user_achievements.get(&account_id).unwrap_or_default();
+                if let Some(unlocked_achievement) = unlocked.iter_mut().find(|unlocked_achievement| unlocked_achievement.achievement_id == achievement_id) {
+                    unlocked_achievement.badge_minted = false;
+                    self.user_achievements.insert(&account_id, &unlocked);
+                }
+                Promise::new(account_id).transfer(NearToken::from_yoctonear(deposit.0));
+                false
+            }
+        }
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // Updates the stats of the account with the win and unlocks the achievements it now meets
+    pub(crate) fn record_achievements(&mut self, account_id: &AccountId, duration: u64) {
+        let day = get_today_day();
+        let mut stats = self.user_stats.get(account_id).unwrap_or_default();
+        stats.wins += 1;
+        if stats.wins == 1 || stats.last_win_day + 1 < day {
+            stats.streak_days = 1;
+        } else if stats.last_win_day + 1 == day {
+            stats.streak_days += 1;
+        }
+        stats.last_win_day = day;
+        self.user_stats.insert(account_id, &stats);
+
+        let mut unlocked = self.user_achievements.get(account_id).unwrap_or_default();
+        let newly_unlocked: Vec<Achievement> = self.achievements.values()
+            .filter(|achievement| !unlocked.iter().any(|current| current.achievement_id == achievement.achievement_id))
+            .filter(|achievement| match achievement.condition {
+                AchievementCondition::Wins { count } => stats.wins >= count,
+                AchievementCondition::WinStreak { days } => stats.streak_days >= days,
+                AchievementCondition::FastWin { max_duration_ms } => duration <= max_duration_ms,
+            })
+            .collect();
+        if newly_unlocked.is_empty() {
+            return;
+        }
+        for achievement in newly_unlocked {
+            log!("{} unlocked achievement {}", account_id, achievement.achievement_id);
+            unlocked.push(UnlockedAchievement {
+                achievement_id: achievement.achievement_id,
+                unlocked_at: get_now_ms(),
+                badge_minted: false,
+            });
+        }
+        self.user_achievements.insert(account_id, &unlocked);
+    }
+
+    fn mint_badge(&self, account_id: &AccountId, achievement: &Achievement, badge_config: &BadgeConfig) -> Promise {
+        let token_metadata = TokenMetadata {
+            title: Some(achievement.title.clone()),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: None,
+            issued_at: Some(get_now_ms().to_string()),
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        };
+        ext_badge_minter::ext(badge_config.badge_contract.clone())
+            .with_static_gas(Gas::from_tgas(15))
+            .with_attached_deposit(NearToken::from_yoctonear(badge_config.mint_deposit.0))
+            .nft_mint(format!("{}:{}", achievement.achievement_id, account_id), account_id.clone(), token_metadata)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_badge_mint(account_id.clone(), achievement.achievement_id.clone(), badge_config.mint_deposit)
+            )
+    }
+}
@@ -0,0 +1,92 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{log, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::difficulty::Difficulty;
+use crate::utils::get_now_ms;
+use crate::roles::Role;
+use crate::{Game, MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// Wins faster than min_game_duration of their difficulty are implausible. They are rejected,
+/// or, with reject_implausible off, settled as usual and counted against the account
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlausibilityPolicy {
+    pub reject_implausible: bool,
+}
+
+impl Default for PlausibilityPolicy {
+    fn default() -> Self {
+        Self {
+            reject_implausible: true,
+        }
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Minimum duration of a win in ms for every difficulty that has one
+    pub fn get_min_game_durations(&self) -> Vec<(Difficulty, u64)> {
+        self.min_game_durations.iter().collect()
+    }
+
+    /// 0 seconds removes the check for the difficulty
+    pub fn set_min_game_duration(&mut self, difficulty: Difficulty, min_duration_seconds: u64) {
+        self.assert_role(Role::Admin);
+        let min_game_duration = min_duration_seconds * 1000;
+        if min_game_duration == 0 {
+            self.min_game_durations.remove(&difficulty);
+            return;
+        }
+        let max_game_duration = self.get_difficulty_config(&difficulty).max_game_duration;
+        require!(min_game_duration < max_game_duration, format!("Minimum duration must be less than the game duration of {} ms", max_game_duration));
+        self.min_game_durations.insert(&difficulty, &min_game_duration);
+    }
+
+    pub fn get_plausibility_policy(&self) -> PlausibilityPolicy {
+        self.plausibility_policy.clone()
+    }
+
+    pub fn set_plausibility_policy(&mut self, plausibility_policy: PlausibilityPolicy) {
+        self.assert_role(Role::Admin);
+        self.plausibility_policy = plausibility_policy;
+    }
+
+    pub fn get_user_flagged_results(&self, account_id: AccountId) -> u32 {
+        self.flagged_results.get(&account_id).unwrap_or_default()
+    }
+
+    /// Accounts with flagged results and how many they have
+    pub fn get_flagged_accounts(&self, from: u64, limit: u64) -> Vec<(AccountId, u32)> {
+        self.flagged_results.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn clear_flagged_results(&mut self, account_id: AccountId) {
+        self.assert_role(Role::Moderator);
+        self.flagged_results.remove(&account_id);
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // Panics on an implausible win, unless the policy only flags it
+    pub(crate) fn check_plausible_win(&mut self, account_id: &AccountId, game: &Game) {
+        let min_game_duration = match self.min_game_durations.get(&game.difficulty) {
+            Some(min_game_duration) => min_game_duration,
+            None => return,
+        };
+        let duration = get_now_ms() - game.start_time;
+        if duration >= min_game_duration {
+            return;
+        }
+        require!(!self.plausibility_policy.reject_implausible, format!("Game finished in {} ms. Minimum for {:?} games is {} ms", duration, game.difficulty, min_game_duration));
+
+        let flagged = self.get_user_flagged_results(account_id.clone()) + 1;
+        log!("Implausible result flagged for {}: finished in {} ms. Flagged results: {}", account_id, duration, flagged);
+        self.flagged_results.insert(account_id, &flagged);
+    }
+}
@@ -0,0 +1,71 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId};
+use crate::structs::ban_json::BanJson;
+use crate::roles::Role;
+use crate::utils::get_now_ms;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct Ban {
+    pub reason: String,
+    pub banned_by: AccountId,
+    pub banned_at: u64,
+    // Permanent when there is no expiry
+    pub expires_at: Option<u64>,
+}
+
+impl Ban {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at.map_or(true, |expires_at| now < expires_at)
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Banned accounts can't start games, buy games or be rewarded. Without a duration the ban is permanent
+    pub fn ban_account(&mut self, account_id: AccountId, reason: String, duration_seconds: Option<u64>) {
+        self.assert_role(Role::Moderator);
+        require!(account_id != self.owner_id, "Owner can't be banned");
+        let now = get_now_ms();
+        log!("Banning {}: {}", account_id, reason);
+        self.bans.insert(&account_id, &Ban {
+            reason,
+            banned_by: env::predecessor_account_id(),
+            banned_at: now,
+            expires_at: duration_seconds.map(|duration_seconds| now + duration_seconds * 1000),
+        });
+    }
+
+    pub fn unban_account(&mut self, account_id: AccountId) {
+        self.assert_role(Role::Moderator);
+        require!(self.bans.remove(&account_id).is_some(), "Account is not banned");
+    }
+
+    /// Ban of the account, unless it already expired
+    pub fn get_ban(&self, account_id: AccountId) -> Option<BanJson> {
+        self.bans.get(&account_id)
+            .filter(|ban| ban.is_active(get_now_ms()))
+            .map(|ban| BanJson::from_ban(account_id, &ban))
+    }
+
+    /// Every ban, including the expired ones that weren't lifted
+    pub fn get_bans(&self, from: u64, limit: u64) -> Vec<BanJson> {
+        self.bans.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(account_id, ban)| BanJson::from_ban(account_id, &ban))
+            .collect()
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn is_banned(&self, account_id: &AccountId) -> bool {
+        self.bans.get(account_id).is_some_and(|ban| ban.is_active(get_now_ms()))
+    }
+
+    pub(crate) fn assert_not_banned(&self, account_id: &AccountId) {
+        require!(!self.is_banned(account_id), "Account is banned");
+    }
+}
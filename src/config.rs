@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Deserializer, Serialize};
+use near_sdk::{log, near_bindgen, require};
+use schemars::JsonSchema;
+use crate::achievements::BadgeConfig;
+use crate::anti_cheat::PlausibilityPolicy;
+use crate::daily_challenge::assert_valid_daily_challenge_rewards;
+use crate::difficulty::Difficulty;
+use crate::extension::ExtensionConfig;
+use crate::race::RaceConfig;
+use crate::reward_limits::RewardLimits;
+use crate::reward_schedule::RewardSchedule;
+use crate::roles::Role;
+use crate::start_limits::StartLimits;
+use crate::timelock::{ConfigChange, ProposalId};
+use crate::{BondPolicy, GameAmount, MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+// Deposits are only a bond against spam, anything above this is most likely a unit mistake
+pub const MAX_MIN_DEPOSIT: Balance = 1_000_000_000_000_000_000_000_000; // 1 NEAR
+
+// Most game cost tiers the store offers
+pub const MAX_GAME_COSTS: usize = 4;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DifficultyLevel {
+    pub difficulty: Difficulty,
+    pub cost_multiplier: GameAmount,
+    pub game_duration_seconds: u64,
+    #[schemars(with = "String")]
+    pub max_reward: U128,
+}
+
+/// Covers the settings of the Admin and PricingManager setters. Fields left out keep their value.
+/// Contract addresses and the timelock delay stay behind their own setters, as they need the owner or the multisig
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigPatch {
+    #[schemars(with = "Option<String>")]
+    pub min_deposit: Option<U128>,
+    // Duration of the Normal level, like set_max_game_duration
+    pub max_game_duration_seconds: Option<u64>,
+    pub max_concurrent_games: Option<u8>,
+    pub bond_policy: Option<BondPolicy>,
+    // Timelocks the whole patch if it raises any limit, like set_reward_limits
+    pub reward_limits: Option<RewardLimits>,
+    pub reward_schedule: Option<RewardSchedule>,
+    // Claim mode, like set_reward_claim_mode
+    pub accumulate_rewards: Option<bool>,
+    #[schemars(with = "Option<String>")]
+    pub min_claim_amount: Option<U128>,
+    pub start_limits: Option<StartLimits>,
+    pub race_config: Option<RaceConfig>,
+    pub plausibility_policy: Option<PlausibilityPolicy>,
+    // Anti cheat minimum of each difficulty listed, in seconds. 0 removes it, like set_min_game_duration
+    pub min_game_durations: Option<Vec<(Difficulty, u64)>>,
+    #[schemars(with = "Option<Vec<String>>")]
+    pub daily_challenge_rewards: Option<Vec<U128>>,
+    // null turns badges off, like set_badge_config(None)
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub badge_config: Option<Option<BadgeConfig>>,
+    // Pricing from here on. A patch with any of them is timelocked as a whole
+    // Replaces the levels listed, the others are kept
+    pub difficulty_levels: Option<Vec<DifficultyLevel>>,
+    // Replaces the whole table
+    #[schemars(with = "Option<Vec<(u8, String)>>")]
+    pub game_costs: Option<Vec<(u8, U128)>>,
+    pub extension_config: Option<ExtensionConfig>,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Validates the whole patch, then applies all of it at once. Pricing and higher reward limits are timelocked,
+    /// so a patch that changes them is proposed as a whole instead, and execute_proposal applies it. Returns that
+    /// proposal
+    pub fn update_config(&mut self, patch: ConfigPatch) -> Option<ProposalId> {
+        self.assert_role(Role::Admin);
+        if self.is_timelocked_patch(&patch) {
+            return Some(self.propose_config_change(ConfigChange::Config { patch: Box::new(patch) }));
+        }
+        self.check_config_patch(&patch);
+        self.apply_config_patch(patch);
+        None
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    fn is_timelocked_patch(&self, patch: &ConfigPatch) -> bool {
+        patch.difficulty_levels.is_some() || patch.game_costs.is_some() || patch.extension_config.is_some()
+            || patch.reward_limits.as_ref().is_some_and(|reward_limits| reward_limits.raises(&self.reward_limits))
+    }
+
+    pub(crate) fn apply_config_patch(&mut self, patch: ConfigPatch) {
+        log!("Updating config: {:?}", patch);
+        if let Some(min_deposit) = patch.min_deposit {
+            self.min_deposit = min_deposit.0;
+        }
+        if let Some(max_concurrent_games) = patch.max_concurrent_games {
+            self.max_concurrent_games = max_concurrent_games;
+        }
+        if let Some(bond_policy) = patch.bond_policy {
+            self.bond_policy = bond_policy;
+        }
+        if let Some(reward_limits) = patch.reward_limits {
+            self.reward_limits = reward_limits;
+        }
+        if let Some(reward_schedule) = patch.reward_schedule {
+            self.reward_schedule = reward_schedule;
+        }
+        if let Some(accumulate_rewards) = patch.accumulate_rewards {
+            self.accumulate_rewards = accumulate_rewards;
+        }
+        if let Some(min_claim_amount) = patch.min_claim_amount {
+            self.min_claim_amount = min_claim_amount.0;
+        }
+        if let Some(start_limits) = patch.start_limits {
+            self.start_limits = start_limits;
+        }
+        if let Some(race_config) = patch.race_config {
+            self.race_config = race_config;
+        }
+        if let Some(plausibility_policy) = patch.plausibility_policy {
+            self.plausibility_policy = plausibility_policy;
+        }
+        if let Some(daily_challenge_rewards) = patch.daily_challenge_rewards {
+            self.daily_challenge_rewards = daily_challenge_rewards.into_iter().map(|reward| reward.0).collect();
+        }
+        if let Some(badge_config) = patch.badge_config {
+            self.badge_config = badge_config;
+        }
+        // Before the durations, which are checked against them
+        for (difficulty, min_duration_seconds) in patch.min_game_durations.unwrap_or_default() {
+            if min_duration_seconds == 0 {
+                self.min_game_durations.remove(&difficulty);
+            } else {
+                self.min_game_durations.insert(&difficulty, &(min_duration_seconds * 1000));
+            }
+        }
+        for level in patch.difficulty_levels.unwrap_or_default() {
+            self.apply_config_change(ConfigChange::DifficultyLevel { level });
+        }
+        if let Some(game_duration_seconds) = patch.max_game_duration_seconds {
+            self.set_game_duration(Difficulty::Normal, game_duration_seconds * 1000);
+        }
+        if let Some(costs) = patch.game_costs {
+            self.apply_config_change(ConfigChange::GameCosts { costs });
+        }
+        if let Some(extension_config) = patch.extension_config {
+            self.extension_config = extension_config;
+        }
+    }
+
+    pub(crate) fn check_config_patch(&self, patch: &ConfigPatch) {
+        if let Some(min_deposit) = patch.min_deposit {
+            require!(min_deposit.0 > 0, "Min deposit must be greater than 0");
+            require!(min_deposit.0 <= MAX_MIN_DEPOSIT, format!("Min deposit can't be more than {} yoctoNEAR", MAX_MIN_DEPOSIT));
+        }
+        if let Some(game_duration_seconds) = patch.max_game_duration_seconds {
+            require!(game_duration_seconds > 0, "Game duration must be greater than 0");
+            let sets_normal_level = patch.difficulty_levels.iter().flatten().any(|level| level.difficulty == Difficulty::Normal);
+            require!(!sets_normal_level, "Set the duration of the Normal level either in max_game_duration_seconds or in difficulty_levels");
+        }
+        if let Some(max_concurrent_games) = patch.max_concurrent_games {
+            require!(max_concurrent_games > 0, "Max concurrent games must be greater than 0");
+        }
+        if let Some(levels) = &patch.difficulty_levels {
+            let difficulties: HashSet<Difficulty> = levels.iter().map(|level| level.difficulty).collect();
+            require!(difficulties.len() == levels.len(), "Each difficulty can only be listed once");
+            levels.iter().for_each(assert_valid_difficulty_level);
+        }
+        if let Some(min_game_durations) = &patch.min_game_durations {
+            let difficulties: HashSet<Difficulty> = min_game_durations.iter().map(|(difficulty, _)| *difficulty).collect();
+            require!(difficulties.len() == min_game_durations.len(), "Each difficulty can only be listed once");
+        }
+        self.check_patched_min_game_durations(patch);
+        if let Some(costs) = &patch.game_costs {
+            assert_valid_game_costs(costs);
+        }
+        if let Some(reward_limits) = &patch.reward_limits {
+            reward_limits.assert_valid();
+        }
+        if let Some(reward_schedule) = &patch.reward_schedule {
+            reward_schedule.assert_valid();
+        }
+        if let Some(start_limits) = &patch.start_limits {
+            start_limits.assert_valid();
+        }
+        if let Some(race_config) = &patch.race_config {
+            race_config.assert_valid();
+        }
+        if let Some(daily_challenge_rewards) = &patch.daily_challenge_rewards {
+            assert_valid_daily_challenge_rewards(daily_challenge_rewards);
+        }
+        if let Some(Some(badge_config)) = &patch.badge_config {
+            badge_config.assert_valid();
+        }
+        if let Some(extension_config) = &patch.extension_config {
+            extension_config.assert_valid();
+        }
+    }
+
+    // Durations and anti cheat minimums can both be in the patch, so each difficulty either touches is checked
+    // with its values once the patch is applied
+    fn check_patched_min_game_durations(&self, patch: &ConfigPatch) {
+        let mut difficulties: HashSet<Difficulty> = patch.difficulty_levels.iter().flatten().map(|level| level.difficulty).collect();
+        difficulties.extend(patch.min_game_durations.iter().flatten().map(|(difficulty, _)| *difficulty));
+        if patch.max_game_duration_seconds.is_some() {
+            difficulties.insert(Difficulty::Normal);
+        }
+        for difficulty in difficulties {
+            let game_duration_seconds = patch.difficulty_levels.iter().flatten()
+                .find(|level| level.difficulty == difficulty)
+                .map(|level| level.game_duration_seconds)
+                .or(patch.max_game_duration_seconds.filter(|_| difficulty == Difficulty::Normal));
+            let game_duration = match game_duration_seconds {
+                Some(game_duration_seconds) => game_duration_seconds * 1000,
+                None => self.get_difficulty_config(&difficulty).max_game_duration,
+            };
+            let min_game_duration = match patch.min_game_durations.iter().flatten().find(|(listed, _)| *listed == difficulty) {
+                Some((_, min_duration_seconds)) => Some(min_duration_seconds * 1000).filter(|min_game_duration| *min_game_duration > 0),
+                None => self.min_game_durations.get(&difficulty),
+            };
+            assert_min_below_game_duration(difficulty, min_game_duration, game_duration);
+        }
+    }
+
+    pub(crate) fn check_difficulty_level(&self, level: &DifficultyLevel) {
+        assert_valid_difficulty_level(level);
+        assert_min_below_game_duration(level.difficulty, self.min_game_durations.get(&level.difficulty), level.game_duration_seconds * 1000);
+    }
+}
+
+fn assert_valid_difficulty_level(level: &DifficultyLevel) {
+    require!(level.cost_multiplier > 0, "Cost multiplier must be greater than 0");
+    require!(level.game_duration_seconds > 0, "Game duration must be greater than 0");
+}
+
+// The anti cheat minimum has to stay below the duration, or every win would be implausible
+fn assert_min_below_game_duration(difficulty: Difficulty, min_game_duration: Option<u64>, game_duration: u64) {
+    if let Some(min_game_duration) = min_game_duration {
+        require!(min_game_duration < game_duration, format!("Game duration of {:?} must be more than its minimum duration of {} ms", difficulty, min_game_duration));
+    }
+}
+
+// Tells a field set to null apart from a field left out, which serde reads as None both
+fn deserialize_some<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+// Same rules insert_game_cost keeps: 1 is always a key and there are at most MAX_GAME_COSTS tiers
+pub(crate) fn assert_valid_game_costs(costs: &[(u8, U128)]) {
+    require!(costs.len() <= MAX_GAME_COSTS, format!("Cannot have more than {} game costs", MAX_GAME_COSTS));
+    let keys: HashSet<u8> = costs.iter().map(|(key, _)| *key).collect();
+    require!(keys.len() == costs.len(), "Game cost keys must be unique");
+    require!(!keys.contains(&0), "Key must be greater than 0");
+    require!(keys.contains(&1), "Game costs must include a single game");
+    require!(costs.iter().all(|(_, cost)| cost.0 > 0), "Game costs must be greater than 0");
+}
@@ -0,0 +1,128 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, require, AccountId, Promise};
+use crate::difficulty::Difficulty;
+use crate::leaderboard::{Leaderboard, LeaderboardEntry, LeaderboardKind, LEADERBOARD_SIZE};
+use crate::structs::daily_challenge_json::DailyChallengeJson;
+use crate::structs::leaderboard_entry_json::LeaderboardEntryJson;
+use crate::utils::get_today_day;
+use crate::roles::Role;
+use crate::{Day, Game, MazeGameBuyerContract, MazeGameBuyerContractExt, SeedId, DAY_MS};
+
+/// Maze shared by every player on a given day. Created by the first player of the day
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DailyChallenge {
+    pub seed_id: SeedId,
+    // Reward of each rank, best rank first. Taken from daily_challenge_rewards when the challenge is created
+    pub rewards: Vec<Balance>,
+    // Earliest completion of each player, counted from the start of the day
+    pub ranking: Leaderboard,
+    pub claimed: Vec<AccountId>,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Starts the attempt of the day on the daily challenge maze. Every account gets one attempt per day
+    #[payable]
+    pub fn start_daily_challenge(&mut self) -> SeedId {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        let day = get_today_day();
+        require!(!self.daily_challenge_players.contains(&(day, account_id.clone())), "Daily challenge already played today");
+
+        let bond = self.assert_min_deposit();
+
+        let storage_usage = env::storage_usage();
+        let challenge = match self.daily_challenges.get(&day) {
+            Some(challenge) => challenge,
+            None => {
+                let challenge = DailyChallenge {
+                    seed_id: self.next_seed_id(),
+                    rewards: self.daily_challenge_rewards.clone(),
+                    ranking: Leaderboard::default(),
+                    claimed: Vec::new(),
+                };
+                self.daily_challenges.insert(&day, &challenge);
+                challenge
+            }
+        };
+        self.daily_challenge_players.insert(&(day, account_id.clone()));
+
+        self.start_game(&account_id, Game {
+            challenge_day: Some(day),
+            ..Game::new(challenge.seed_id, Difficulty::Normal, bond)
+        }, storage_usage);
+        challenge.seed_id
+    }
+
+    pub fn get_daily_challenge(&self, day: Day) -> Option<DailyChallengeJson> {
+        self.daily_challenges.get(&day).map(|challenge| DailyChallengeJson::from_challenge(day, &challenge))
+    }
+
+    pub fn get_daily_challenge_ranking(&self, day: Day, from: u64, limit: u64) -> Vec<LeaderboardEntryJson> {
+        let challenge = self.daily_challenges.get(&day).expect("No daily challenge that day");
+        challenge.ranking.entries.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(LeaderboardEntryJson::from)
+            .collect()
+    }
+
+    pub fn has_played_daily_challenge(&self, account_id: AccountId, day: Day) -> bool {
+        self.daily_challenge_players.contains(&(day, account_id))
+    }
+
+    pub fn get_daily_challenge_rewards(&self) -> Vec<U128> {
+        self.daily_challenge_rewards.iter().map(|reward| U128(*reward)).collect()
+    }
+
+    /// Rewards of the top players, best rank first. Only challenges created afterwards use them
+    pub fn set_daily_challenge_rewards(&mut self, rewards: Vec<U128>) {
+        self.assert_role(Role::Admin);
+        assert_valid_daily_challenge_rewards(&rewards);
+        self.daily_challenge_rewards = rewards.into_iter().map(|reward| reward.0).collect();
+    }
+
+    /// Mints the reward of the caller's rank once the day of the challenge is over
+    pub fn claim_daily_challenge_reward(&mut self, day: Day) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        self.assert_settlement_not_paused();
+        require!(day < get_today_day(), "Daily challenge has not ended yet");
+        let mut challenge = self.daily_challenges.get(&day).expect("No daily challenge that day");
+        require!(!challenge.claimed.contains(&account_id), "Daily challenge reward already claimed");
+        let rank = challenge.ranking.entries.iter()
+            .position(|entry| entry.account_id == account_id)
+            .expect("User is not ranked in the daily challenge");
+        let reward = challenge.rewards.get(rank).copied().unwrap_or(0);
+        require!(reward > 0, "No reward for this rank");
+        self.consume_daily_reward_limits(&account_id, reward);
+
+        challenge.claimed.push(account_id.clone());
+        self.daily_challenges.insert(&day, &challenge);
+        log!("Daily challenge {} rank {} rewards {} to {}", day, rank + 1, reward, account_id);
+        self.mint_reward(account_id, reward, None)
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // Only wins settled before the day ends count. Like tournaments, players are timed from the start of the
+    // day, so reading the seed of an earlier player and solving it before starting gives no edge
+    pub(crate) fn record_daily_challenge_result(&mut self, day: Day, mut entry: LeaderboardEntry) {
+        if entry.end_time >= (day + 1) * DAY_MS {
+            log!("Daily challenge {} already ended. Result not recorded", day);
+            return;
+        }
+        entry.duration = entry.end_time - day * DAY_MS;
+        let mut challenge = self.daily_challenges.get(&day).expect("No daily challenge that day");
+        challenge.ranking.record(LeaderboardKind::CompletionTime, entry);
+        self.daily_challenges.insert(&day, &challenge);
+    }
+}
+
+pub(crate) fn assert_valid_daily_challenge_rewards(rewards: &[U128]) {
+    require!(rewards.len() <= LEADERBOARD_SIZE, format!("Daily challenge can't reward more than {} ranks", LEADERBOARD_SIZE));
+}
@@ -1,44 +1,83 @@
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
-use near_sdk::{near_bindgen, AccountId, json_types::U128, env, PromiseOrValue};
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::{near_bindgen, log, AccountId, json_types::U128, env, PromiseOrValue};
+use near_sdk::serde::{Deserialize, Serialize};
+use crate::race::RaceId;
+use crate::tournament::TournamentId;
 use crate::utils::safe_u128_to_u16;
 use crate::MazeGameBuyerContractExt;
-use crate::MazeGameBuyerContract;
+use crate::{GameId, MazeGameBuyerContract};
+
+// An empty msg buys games. Anything else must be one of these actions, e.g. {"action":"enter_tournament","tournament_id":1}
+// or {"action":"create_race"}, where the whole amount is the stake, or {"action":"extend","seconds":60}
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+pub enum TransferAction {
+    EnterTournament { tournament_id: TournamentId },
+    CreateRace {},
+    JoinRace { race_id: RaceId },
+    // Extends the latest ongoing game unless a game id is given
+    Extend { seconds: u64, game_id: Option<GameId> },
+}
 
-#[allow(unused_variables)]
 #[near_bindgen]
 impl FungibleTokenReceiver for MazeGameBuyerContract {
     fn ft_on_transfer(
         &mut self,
         sender_id: AccountId,
         amount: U128,
-        #[allow(unused_variables)]
         msg: String,
     ) -> PromiseOrValue<U128> {
         let ft_token = env::predecessor_account_id();
         assert!(ft_token == self.cheddar_contract, "Only cheddar is accepted {}", self.cheddar_contract);
+        if self.pause_flags.purchases {
+            log!("Purchases are paused. Refunding {}", amount.0);
+            return PromiseOrValue::Value(amount);
+        }
+        if self.is_banned(&sender_id) {
+            log!("{} is banned. Refunding {}", sender_id, amount.0);
+            return PromiseOrValue::Value(amount);
+        }
+        if msg.is_empty() {
+            return PromiseOrValue::Value(U128::from(self.buy_games(sender_id, amount.0)));
+        }
+        let action: TransferAction = near_sdk::serde_json::from_str(&msg).expect("Invalid msg");
+        let remaining_cheddar = match action {
+            TransferAction::EnterTournament { tournament_id } => self.enter_tournament(sender_id, tournament_id, amount.0),
+            TransferAction::CreateRace {} => {
+                self.create_race(sender_id, amount.0);
+                0
+            }
+            TransferAction::JoinRace { race_id } => self.join_race(sender_id, race_id, amount.0),
+            TransferAction::Extend { seconds, game_id } => self.buy_extension(sender_id, game_id, seconds, amount.0),
+        };
+        PromiseOrValue::Value(U128::from(remaining_cheddar))
+    }
+
+
+}
+
+impl MazeGameBuyerContract {
+
+    /// Returns the cheddar that wasn't enough to buy one more game
+    fn buy_games(&mut self, sender_id: AccountId, amount: Balance) -> Balance {
         let mut game_promo_num = 0;
         // game_costs should always be limited to 4 key-value pairs
         for (key, value) in self.game_costs.into_iter() {
-            if amount.0 / *key as u128 >= *value {
+            if amount / *key as u128 >= *value {
                 game_promo_num = *key;
             } else {
                 break;
             }
         }
         let single_game_cost = self.game_costs.get(&1).unwrap();
-        assert!(game_promo_num != 0, "Insufficient cheddar sent {}. Sent at least {} cheddar", amount.0, single_game_cost);
-        if game_promo_num == 0 {
-            return PromiseOrValue::Value(amount);
-        }
+        assert!(game_promo_num != 0, "Insufficient cheddar sent {}. Sent at least {} cheddar", amount, single_game_cost);
         let game_cost = self.get_game_cost(game_promo_num);
-        let games_bought = amount.0 / game_cost;
-        let games_bought_u16 = safe_u128_to_u16(games_bought).expect(format!("Too many games bought. Limit is {}", u16::MAX).as_str());
+        let games_bought = amount / game_cost;
+        let games_bought_u16 = safe_u128_to_u16(games_bought).unwrap_or_else(|_| panic!("Too many games bought. Limit is {}", u16::MAX));
         self.add_games_to_user(sender_id.clone(), games_bought_u16);
 
-        let remaining_cheddar = amount.0 % game_cost;
-        PromiseOrValue::Value(U128::from(remaining_cheddar))
-
+        self.treasury += games_bought * game_cost;
+        amount % game_cost
     }
-    
-    
-}
\ No newline at end of file
+}
@@ -0,0 +1,43 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::utils::to_yocto_u8;
+use crate::{GameAmount, MIN_MS};
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct DifficultyConfig {
+    // Amount of games (free or paid) a single game of this level consumes
+    pub cost_multiplier: GameAmount,
+    pub max_game_duration: u64,
+    pub max_reward: Balance,
+}
+
+pub fn default_difficulty_levels() -> [(Difficulty, DifficultyConfig); 3] {
+    [
+        (Difficulty::Easy, DifficultyConfig {
+            cost_multiplier: 1,
+            max_game_duration: 5 * MIN_MS,
+            max_reward: to_yocto_u8(5).0,
+        }),
+        (Difficulty::Normal, DifficultyConfig {
+            cost_multiplier: 1,
+            max_game_duration: 3 * MIN_MS,
+            max_reward: to_yocto_u8(10).0,
+        }),
+        (Difficulty::Hard, DifficultyConfig {
+            cost_multiplier: 2,
+            max_game_duration: 2 * MIN_MS,
+            max_reward: to_yocto_u8(25).0,
+        }),
+    ]
+}
@@ -0,0 +1,90 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::utils::to_yocto_u8;
+use crate::roles::Role;
+use crate::timelock::{ConfigChange, ProposalId};
+use crate::{GameId, MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// Extra time players can buy for a running game, with cheddar or by spending one paid game
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtensionConfig {
+    pub max_extensions: u8,
+    // Longest extension that can be bought with cheddar at once
+    pub max_seconds: u64,
+    #[schemars(with = "String")]
+    pub cost_per_minute: U128,
+    pub seconds_per_paid_game: u64,
+}
+
+impl ExtensionConfig {
+    // max_extensions set to 0 is how extensions are turned off, so the rest always has to be usable
+    pub fn assert_valid(&self) {
+        require!(self.max_seconds > 0, "Max extension must be longer than 0 seconds");
+        require!(self.cost_per_minute.0 > 0, "Extension cost must be greater than 0");
+        require!(self.seconds_per_paid_game > 0, "Extension for a paid game must be longer than 0 seconds");
+    }
+}
+
+impl Default for ExtensionConfig {
+    fn default() -> Self {
+        Self {
+            max_extensions: 2,
+            max_seconds: 120,
+            cost_per_minute: to_yocto_u8(5),
+            seconds_per_paid_game: 60,
+        }
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_extension_config(&self) -> ExtensionConfig {
+        self.extension_config.clone()
+    }
+
+    /// Timelocked like the game costs, as it prices the extensions
+    pub fn set_extension_config(&mut self, extension_config: ExtensionConfig) -> ProposalId {
+        self.assert_role(Role::PricingManager);
+        self.propose_config_change(ConfigChange::ExtensionConfig { extension_config })
+    }
+
+    /// Spends one paid game to extend an ongoing game of the caller, the latest one unless a game id is given
+    pub fn extend_game_with_paid_game(&mut self, game_id: Option<GameId>) {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        self.assert_purchases_not_paused();
+        let remaining_paid_games = self.get_user_remaining_paid_games(&account_id);
+        require!(remaining_paid_games > 0, "No paid games remaining for the user");
+        self.user_remaining_paid_games.insert(&account_id, &(remaining_paid_games - 1));
+        self.extend_game(&account_id, game_id, self.extension_config.seconds_per_paid_game);
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    /// Returns the part of the transfer above the price of the extension
+    pub(crate) fn buy_extension(&mut self, account_id: AccountId, game_id: Option<GameId>, seconds: u64, amount: Balance) -> Balance {
+        require!(seconds <= self.extension_config.max_seconds, format!("Extension can't be longer than {} seconds", self.extension_config.max_seconds));
+        let cost = self.extension_config.cost_per_minute.0 * seconds as u128 / 60;
+        require!(amount >= cost, format!("Insufficient cheddar sent {}. Extension costs {}", amount, cost));
+        self.extend_game(&account_id, game_id, seconds);
+        self.treasury += cost;
+        amount - cost
+    }
+
+    fn extend_game(&mut self, account_id: &AccountId, game_id: Option<GameId>, seconds: u64) {
+        require!(seconds > 0, "Extension must be longer than 0 seconds");
+        let (game_id, mut ongoing_game) = self.expect_ongoing_game(account_id, game_id);
+        require!(ongoing_game.extensions < self.extension_config.max_extensions, format!("Game can't be extended more than {} times", self.extension_config.max_extensions));
+        ongoing_game.extensions += 1;
+        ongoing_game.extended_ms += seconds * 1000;
+        log!("Game {} of {} extended by {} seconds", game_id, account_id, seconds);
+        self.ongoing_games.insert(&(account_id.clone(), game_id), &ongoing_game);
+    }
+}
@@ -1,7 +1,16 @@
 use near_sdk::{ext_contract, json_types::U128, AccountId};
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
 
 // External contract interface for the Maze Minter contract
+#[allow(dead_code)]
 #[ext_contract(ext_maze_minter)]
 pub trait ExtMazeMinter {
     fn mint(&mut self, recipient: AccountId, amount: U128, referral: Option<AccountId>) -> (u128, u128);
-}
\ No newline at end of file
+}
+
+// Soulbound NEP-171 contract achievements are minted on. Argument names follow the reference nft_mint
+#[allow(dead_code)]
+#[ext_contract(ext_badge_minter)]
+pub trait ExtBadgeMinter {
+    fn nft_mint(&mut self, token_id: String, token_owner_id: AccountId, token_metadata: TokenMetadata);
+}
@@ -0,0 +1,55 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use schemars::JsonSchema;
+use crate::SeedId;
+
+// Older games are overwritten once an account reaches this amount of archived games
+pub const GAME_HISTORY_SIZE: usize = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum GameOutcome {
+    Won,
+    Lost,
+    // Given up by the player, with forfeit_game or by starting another game
+    Forfeited,
+    // Ran out of time and settled by settle_expired_games
+    Expired,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct GameRecord {
+    pub seed_id: SeedId,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub outcome: GameOutcome,
+    pub reward: Balance,
+    pub referral: Option<AccountId>,
+}
+
+/// Ring buffer of the last GAME_HISTORY_SIZE finished games of an account
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct GameHistory {
+    records: Vec<GameRecord>,
+    // Index of the oldest record once the buffer is full
+    start: u32,
+}
+
+impl GameHistory {
+    pub fn push(&mut self, record: GameRecord) {
+        if self.records.len() < GAME_HISTORY_SIZE {
+            self.records.push(record);
+        } else {
+            self.records[self.start as usize] = record;
+            self.start = (self.start + 1) % GAME_HISTORY_SIZE as u32;
+        }
+    }
+
+    /// Records from oldest to newest, skipping the first `from` ones
+    pub fn iter_from(&self, from: usize) -> impl Iterator<Item = &GameRecord> {
+        let len = self.records.len();
+        (from..len).map(move |index| &self.records[(self.start as usize + index) % len])
+    }
+}
@@ -10,8 +10,200 @@ impl MazeGameBuyerContract {
         );
     }
 
+    // The callback records the reward as pending if the mint fails
+    pub(crate) fn mint_reward(&self, account_id: AccountId, amount: Balance, referral: Option<AccountId>) -> Promise {
+        ext_maze_minter::ext(self.maze_minter_contract.clone())
+            .with_static_gas(Gas::from_tgas(30))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .mint(account_id.clone(), U128(amount), referral.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .on_mint_callback(account_id, U128(amount), referral)
+            )
+    }
+
+    /// Mixes the randomness of the block into the seed, so the maze of a game can't be known, and solved,
+    /// before it starts
+    // Every game start takes a bond of at least min_deposit. Returns the bond
+    pub(crate) fn assert_min_deposit(&self) -> Balance {
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit >= self.min_deposit, "Deposit must be at least {} yoctoNEAR", self.min_deposit);
+        deposit
+    }
+
+    pub(crate) fn next_seed_id(&mut self) -> SeedId {
+        self.seed_id += 1;
+        let mut seed_input = env::random_seed();
+        seed_input.extend_from_slice(&self.seed_id.to_le_bytes());
+        let hash = env::sha256(&seed_input);
+        SeedId::from_le_bytes(hash[..8].try_into().unwrap())
+    }
+
     pub(crate) fn get_game_cost(&self, game_promo_num: u8) -> Balance {
-        self.game_costs.get(&game_promo_num).expect("Game cost not found").clone()
+        *self.game_costs.get(&game_promo_num).expect("Game cost not found")
+    }
+
+    pub(crate) fn get_game_cost_table(&self) -> Vec<(u8, Balance)> {
+        self.game_costs.iter().map(|(key, value)| (*key, *value)).collect()
+    }
+
+    // buy_games goes through the tiers in order and stops at the first one it can't afford, so the table
+    // is always stored sorted by key
+    pub(crate) fn replace_game_costs(&mut self, mut costs: Vec<(u8, Balance)>) {
+        costs.sort_by_key(|(key, _)| *key);
+        self.game_costs.clear();
+        for (key, value) in costs {
+            self.game_costs.insert(key, value);
+        }
+    }
+
+    pub(crate) fn archive_game(&mut self, account_id: &AccountId, record: GameRecord) {
+        let mut history = self.game_history.get(account_id).unwrap_or_default();
+        history.push(record);
+        self.game_history.insert(account_id, &history);
+    }
+
+    pub(crate) fn record_leaderboards(&mut self, entry: LeaderboardEntry) {
+        let day = get_today_day();
+        for period in LeaderboardPeriod::ALL {
+            for kind in LeaderboardKind::ALL {
+                let key = (period, kind);
+                let mut leaderboard = self.leaderboards.get(&key).unwrap_or_default();
+                // A new day or week starts with an empty board, so old periods don't pile up in storage
+                if leaderboard.period_index != period.index(day) {
+                    leaderboard = Leaderboard {
+                        period_index: period.index(day),
+                        entries: vec![],
+                    };
+                }
+                leaderboard.record(kind, entry.clone());
+                self.leaderboards.insert(&key, &leaderboard);
+            }
+        }
+    }
+
+    pub(crate) fn is_bond_kept(&self, outcome: &GameOutcome) -> bool {
+        match outcome {
+            GameOutcome::Forfeited => self.bond_policy.keep_on_forfeit,
+            GameOutcome::Expired => self.bond_policy.keep_on_expiry,
+            GameOutcome::Won | GameOutcome::Lost => false,
+        }
+    }
+
+    pub(crate) fn get_difficulty_config(&self, difficulty: &Difficulty) -> DifficultyConfig {
+        self.difficulty_levels.get(difficulty).expect("Difficulty level not found")
+    }
+
+    // Bought extensions come on top of the base duration, they only push the deadline
+    pub(crate) fn get_game_duration(&self, game: &Game) -> u64 {
+        self.get_base_game_duration(game) + game.extended_ms
+    }
+
+    pub(crate) fn get_base_game_duration(&self, game: &Game) -> u64 {
+        self.get_difficulty_config(&game.difficulty).max_game_duration
+    }
+
+    // The anti cheat minimum has to stay below the duration
+    pub(crate) fn set_game_duration(&mut self, difficulty: Difficulty, game_duration: u64) {
+        require!(game_duration > 0, "Game duration must be greater than 0");
+        if let Some(min_game_duration) = self.min_game_durations.get(&difficulty) {
+            require!(min_game_duration < game_duration, format!("Game duration of {:?} must be more than its minimum duration of {} ms", difficulty, min_game_duration));
+        }
+        let mut config = self.get_difficulty_config(&difficulty);
+        config.max_game_duration = game_duration;
+        self.difficulty_levels.insert(&difficulty, &config);
+    }
+
+    pub(crate) fn get_ongoing_game_ids(&self, account_id: &AccountId) -> Vec<GameId> {
+        self.user_game_ids.get(account_id).unwrap_or_default()
+    }
+
+    /// Game of the account, unless it already ran out of time
+    pub(crate) fn get_ongoing_game(&self, account_id: &AccountId, game_id: GameId) -> Option<Game> {
+        let ongoing_game = self.ongoing_games.get(&(account_id.clone(), game_id))?;
+        let now = get_now_ms();
+        log!("Now: {}", now);
+        log!("Start time: {}", ongoing_game.start_time);
+        assert!(now >= ongoing_game.start_time, "Start time is in the future");
+        if now - ongoing_game.start_time >= self.get_game_duration(&ongoing_game) {
+            return None;
+        }
+        Some(ongoing_game)
+    }
+
+    /// The given game, or the latest ongoing game of the account. Panics if there is none
+    pub(crate) fn expect_ongoing_game(&self, account_id: &AccountId, game_id: Option<GameId>) -> (GameId, Game) {
+        let game_id = game_id
+            .or_else(|| self.get_ongoing_game_ids(account_id).last().copied())
+            .expect("No ongoing game for the user");
+        let ongoing_game = self.get_ongoing_game(account_id, game_id).expect("No ongoing game for the user");
+        (game_id, ongoing_game)
+    }
+
+    /// Adds the game to the ongoing games of the account. Games of the account that ran out of time
+    /// are settled as expired first. Panics if the account still has max_concurrent_games ongoing, so a game
+    /// is never given up without the player asking for it
+    /// `storage_usage` is the storage used when the call started. What the call added since then is recorded
+    /// in the game, except for the games settled here, which pay for their own storage
+    pub(crate) fn start_game(&mut self, account_id: &AccountId, mut game: Game, storage_usage: StorageUsage) -> GameId {
+        self.assert_game_starts_not_paused();
+        let settling_storage_usage = env::storage_usage();
+        for game_id in self.get_ongoing_game_ids(account_id) {
+            let ongoing_game = self.ongoing_games.get(&(account_id.clone(), game_id)).unwrap();
+            if self.get_ongoing_game(account_id, game_id).is_none() {
+                self.settle_game(account_id.clone(), game_id, ongoing_game, GameOutcome::Expired, U128(0), None);
+            }
+        }
+        require!(
+            self.get_ongoing_game_ids(account_id).len() < self.max_concurrent_games as usize,
+            format!("Limit of {} ongoing games reached. Finish or forfeit a game first", self.max_concurrent_games)
+        );
+        let settled_bytes = env::storage_usage() as i64 - settling_storage_usage as i64;
+
+        self.last_game_id += 1;
+        let key = (account_id.clone(), self.last_game_id);
+        self.ongoing_games.insert(&key, &game);
+        let mut game_ids = self.get_ongoing_game_ids(account_id);
+        game_ids.push(self.last_game_id);
+        self.user_game_ids.insert(account_id, &game_ids);
+        // Same size as before, so storing it again doesn't change the usage
+        game.storage_usage = (env::storage_usage() as i64 - storage_usage as i64 - settled_bytes).max(0) as StorageUsage;
+        self.ongoing_games.insert(&key, &game);
+        self.last_game_id
+    }
+
+    // The bond pays for the storage the game leaves behind, like the records of the player and its history entry
+    pub(crate) fn refund_bond(&self, account_id: &AccountId, game: &Game, outcome: &GameOutcome, settling_storage_usage: StorageUsage) {
+        if game.bond == 0 || self.is_bond_kept(outcome) {
+            return;
+        }
+        let settled_bytes = env::storage_usage() as i64 - settling_storage_usage as i64;
+        let added_bytes = (game.storage_usage as i64 + settled_bytes).max(0) as u128;
+        let refund = game.bond.saturating_sub(added_bytes * env::storage_byte_cost().as_yoctonear());
+        if refund > 0 {
+            Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(refund));
+        }
+    }
+
+    pub(crate) fn remove_game(&mut self, account_id: &AccountId, game_id: GameId) {
+        self.ongoing_games.remove(&(account_id.clone(), game_id));
+        let mut game_ids = self.get_ongoing_game_ids(account_id);
+        game_ids.retain(|id| *id != game_id);
+        if game_ids.is_empty() {
+            self.user_game_ids.remove(account_id);
+        } else {
+            self.user_game_ids.insert(account_id, &game_ids);
+        }
+    }
+
+    // The time share is based on the duration without extensions, so buying time doesn't raise the reward.
+    // A win in the bought time gets the minimum time share
+    pub(crate) fn compute_reward(&self, game: &Game, elapsed_ms: u64, collected_items: u16) -> Balance {
+        let max_reward = self.get_difficulty_config(&game.difficulty).max_reward;
+        let base_duration = self.get_base_game_duration(game);
+        let elapsed_ms = if elapsed_ms < self.get_game_duration(game) { elapsed_ms.min(base_duration - 1) } else { elapsed_ms };
+        self.reward_schedule.compute(max_reward, base_duration, elapsed_ms, collected_items)
     }
     
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use schemars::JsonSchema;
+use crate::{Day, SeedId};
+
+// Only the best LEADERBOARD_SIZE accounts are kept on each board
+pub const LEADERBOARD_SIZE: usize = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    Daily,
+    Weekly,
+    AllTime,
+}
+
+impl LeaderboardPeriod {
+    pub const ALL: [LeaderboardPeriod; 3] = [LeaderboardPeriod::Daily, LeaderboardPeriod::Weekly, LeaderboardPeriod::AllTime];
+
+    /// Boards are reset whenever this index changes
+    pub fn index(&self, day: Day) -> u64 {
+        match self {
+            LeaderboardPeriod::Daily => day,
+            LeaderboardPeriod::Weekly => day / 7,
+            LeaderboardPeriod::AllTime => 0,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum LeaderboardKind {
+    Reward,
+    CompletionTime,
+}
+
+impl LeaderboardKind {
+    pub const ALL: [LeaderboardKind; 2] = [LeaderboardKind::Reward, LeaderboardKind::CompletionTime];
+
+    // Strict comparison, so on a tie the entry that got there first keeps the better rank
+    fn is_better(&self, entry: &LeaderboardEntry, other: &LeaderboardEntry) -> bool {
+        match self {
+            LeaderboardKind::Reward => entry.reward > other.reward,
+            LeaderboardKind::CompletionTime => entry.duration < other.duration,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct LeaderboardEntry {
+    pub account_id: AccountId,
+    pub seed_id: SeedId,
+    pub reward: Balance,
+    // Milliseconds between the game start and its settlement. Tournaments and daily challenges count from
+    // their own start
+    pub duration: u64,
+    pub end_time: u64,
+}
+
+/// Best result of each account for one period, sorted from best to worst
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Leaderboard {
+    pub period_index: u64,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn record(&mut self, kind: LeaderboardKind, entry: LeaderboardEntry) {
+        if let Some(position) = self.entries.iter().position(|current| current.account_id == entry.account_id) {
+            if !kind.is_better(&entry, &self.entries[position]) {
+                return;
+            }
+            self.entries.remove(position);
+        }
+        let rank = self.entries.iter()
+            .position(|current| kind.is_better(&entry, current))
+            .unwrap_or(self.entries.len());
+        if rank >= LEADERBOARD_SIZE {
+            return;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+    }
+}
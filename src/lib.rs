@@ -1,30 +1,76 @@
 use external::ext_maze_minter;
 use near_contract_standards::fungible_token::Balance;
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::store::IterableMap;
 // Find all our documentation at https://docs.near.org
 use near_sdk::{
-    env, log, near_bindgen, require, AccountId, Gas, NearToken, PanicOnDefault, Promise
+    env, log, near_bindgen, require, AccountId, Gas, NearToken, PanicOnDefault, Promise, StorageUsage
 };
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use schemars::JsonSchema;
 use utils::{get_now_ms, get_today_day, to_yocto_u8};
 use structs::game_struct_json::GameJson;
+use structs::game_record_json::GameRecordJson;
+use structs::leaderboard_entry_json::LeaderboardEntryJson;
+use history::{GameHistory, GameOutcome, GameRecord};
+use leaderboard::{Leaderboard, LeaderboardEntry, LeaderboardKind, LeaderboardPeriod};
+use tournament::{Tournament, TournamentId, TournamentPlayer};
+use difficulty::{default_difficulty_levels, Difficulty, DifficultyConfig};
+use structs::difficulty_config_json::DifficultyConfigJson;
+use config::DifficultyLevel;
+use reward_limits::{DailyReward, RewardLimits};
+use reward_schedule::{GameResult, RewardSchedule};
+use pending_rewards::PendingReward;
+use anti_cheat::PlausibilityPolicy;
+use ban::Ban;
+use start_limits::{GameStarts, StartLimits};
+use daily_challenge::DailyChallenge;
+use race::{Race, RaceConfig, RaceId};
+use extension::ExtensionConfig;
+use roles::Role;
+use pause::PauseFlags;
+use timelock::{ConfigChange, Proposal, ProposalId};
+use multisig::{MultisigConfig, MultisigRequest, RequestId};
+use achievements::{default_achievements, Achievement, AchievementId, BadgeConfig, PlayerStats, UnlockedAchievement};
 
 mod internal;
 mod deposit;
 mod utils;
 mod structs;
 mod external;
+mod history;
+mod leaderboard;
+mod tournament;
+mod difficulty;
+mod reward_limits;
+mod reward_schedule;
+mod pending_rewards;
+mod reward_balance;
+mod anti_cheat;
+mod ban;
+mod start_limits;
+mod daily_challenge;
+mod race;
+mod extension;
+mod achievements;
+mod roles;
+mod ownership;
+mod pause;
+mod timelock;
+mod treasury;
+mod multisig;
+mod config;
 
 pub type Day = u64; // Having this data type, means how many days passed since 01/01/1970
 pub type GameAmount = u16;
 pub type SeedId = u64;
+pub type GameId = u64;
 
 const DAY_MS: u64 = 24 * 3600 * 1000;
 const MIN_MS: u64 = 60 * 1000;
+const DEFAULT_MAX_CONCURRENT_GAMES: u8 = 3;
 
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -32,19 +78,80 @@ pub struct FreeGameInfo {
 	day: Day,
 	amount: GameAmount,
 }
-#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, JsonSchema)]
+/// Whether the deposit attached to start a game is kept by the contract instead of being refunded.
+/// Games that are won or lost always get their deposit back
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondPolicy {
+    keep_on_forfeit: bool,
+    keep_on_expiry: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, JsonSchema)]
 pub struct Game {
 	seed_id: SeedId,
 	start_time: u64,
+    is_ending_game: bool,
+    tournament_id: Option<TournamentId>,
+    // Day of the daily challenge the game belongs to
+    challenge_day: Option<Day>,
+    race_id: Option<RaceId>,
+    difficulty: Difficulty,
+    // NEAR attached when the game started. Refunded on settlement depending on the bond policy
+    bond: Balance,
+    // Time bought on top of the duration of the game
+    extensions: u8,
+    extended_ms: u64,
+    // Bytes the start of the game added to the storage. Paid out of the bond, with what the settlement adds
+    storage_usage: StorageUsage,
+}
+
+// Game as it was stored before tournaments. Only used by migrate
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldGame {
+	seed_id: SeedId,
+	start_time: u64,
     is_ending_game: bool
 }
 
-impl Default for Game {
-    fn default() -> Self {
+impl Game {
+    /// A game starting now. Tournament, daily challenge and race games set their id on top
+    pub fn new(seed_id: SeedId, difficulty: Difficulty, bond: Balance) -> Self {
+        Self {
+            seed_id,
+            start_time: env::block_timestamp_ms(),
+            is_ending_game: false,
+            tournament_id: None,
+            challenge_day: None,
+            race_id: None,
+            difficulty,
+            bond,
+            extensions: 0,
+            extended_ms: 0,
+            storage_usage: 0,
+        }
+    }
+
+    /// Tournament, daily challenge and race wins are only ranked. Their prizes are paid separately
+    pub fn mints_reward(&self) -> bool {
+        self.tournament_id.is_none() && self.challenge_day.is_none() && self.race_id.is_none()
+    }
+}
+
+impl From<OldGame> for Game {
+    fn from(old_game: OldGame) -> Self {
         Self {
-            seed_id: 0,
-            start_time: 0,
-            is_ending_game: false
+            seed_id: old_game.seed_id,
+            start_time: old_game.start_time,
+            is_ending_game: old_game.is_ending_game,
+            tournament_id: None,
+            challenge_day: None,
+            race_id: None,
+            difficulty: Difficulty::Normal,
+            bond: 0,
+            extensions: 0,
+            extended_ms: 0,
+            storage_usage: 0,
         }
     }
 }
@@ -57,11 +164,59 @@ pub struct MazeGameBuyerContract {
     game_costs: IterableMap<u8, Balance>,
     user_remaining_free_games: UnorderedMap<AccountId, FreeGameInfo>,
     user_remaining_paid_games: UnorderedMap<AccountId, GameAmount>,
+    // Amount of seeds handed out. The seeds themselves come from next_seed_id
     seed_id: SeedId,
     min_deposit: Balance,
-    ongoing_games: UnorderedMap<AccountId, Game>,
+    ongoing_games: UnorderedMap<(AccountId, GameId), Game>,
+    // Ids of the ongoing games of each account, oldest first
+    user_game_ids: LookupMap<AccountId, Vec<GameId>>,
+    last_game_id: GameId,
+    max_concurrent_games: u8,
     maze_minter_contract: AccountId,
-    max_game_duration: u64,
+    difficulty_levels: UnorderedMap<Difficulty, DifficultyConfig>,
+    bond_policy: BondPolicy,
+    reward_limits: RewardLimits,
+    user_daily_rewards: LookupMap<AccountId, DailyReward>,
+    daily_minted: DailyReward,
+    reward_schedule: RewardSchedule,
+    pending_rewards: LookupMap<AccountId, Vec<PendingReward>>,
+    accumulate_rewards: bool,
+    min_claim_amount: Balance,
+    reward_balances: LookupMap<AccountId, Vec<PendingReward>>,
+    min_game_durations: UnorderedMap<Difficulty, u64>,
+    plausibility_policy: PlausibilityPolicy,
+    flagged_results: UnorderedMap<AccountId, u32>,
+    bans: UnorderedMap<AccountId, Ban>,
+    start_limits: StartLimits,
+    game_starts: LookupMap<AccountId, GameStarts>,
+    daily_challenges: LookupMap<Day, DailyChallenge>,
+    daily_challenge_players: LookupSet<(Day, AccountId)>,
+    daily_challenge_rewards: Vec<Balance>,
+    races: UnorderedMap<RaceId, Race>,
+    last_race_id: RaceId,
+    race_config: RaceConfig,
+    extension_config: ExtensionConfig,
+    achievements: UnorderedMap<AchievementId, Achievement>,
+    user_achievements: LookupMap<AccountId, Vec<UnlockedAchievement>>,
+    user_stats: LookupMap<AccountId, PlayerStats>,
+    badge_config: Option<BadgeConfig>,
+    roles: UnorderedSet<(Role, AccountId)>,
+    pending_owner_id: Option<AccountId>,
+    pause_flags: PauseFlags,
+    proposals: UnorderedMap<ProposalId, Proposal>,
+    last_proposal_id: ProposalId,
+    timelock_delay: u64,
+    multisig_config: Option<MultisigConfig>,
+    multisig_requests: UnorderedMap<RequestId, MultisigRequest>,
+    last_request_id: RequestId,
+    game_history: LookupMap<AccountId, GameHistory>,
+    leaderboards: LookupMap<(LeaderboardPeriod, LeaderboardKind), Leaderboard>,
+    tournaments: UnorderedMap<TournamentId, Tournament>,
+    tournament_players: LookupMap<(TournamentId, AccountId), TournamentPlayer>,
+    last_tournament_id: TournamentId,
+    // Cheddar owned by the contract, out of the pools and stakes it holds for players
+    treasury: Balance,
+    unpaid_cheddar: LookupMap<AccountId, Balance>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
@@ -73,8 +228,9 @@ pub struct OldMazeGameBuyerContract {
     user_remaining_paid_games: UnorderedMap<AccountId, GameAmount>,
     seed_id: SeedId,
     min_deposit: Balance,
-    ongoing_games: UnorderedMap<AccountId, Game>,
+    ongoing_games: UnorderedMap<AccountId, OldGame>,
     maze_minter_contract: AccountId,
+    max_game_duration: u64,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -86,7 +242,20 @@ pub struct ContractState {
     game_costs: Vec<[String; 2]>,
     seed_id: SeedId,
     min_deposit: String,
+    // Duration of the Normal level
     max_game_duration: u64,
+    difficulty_levels: Vec<DifficultyConfigJson>,
+    bond_policy: BondPolicy,
+    reward_limits: RewardLimits,
+    reward_schedule: RewardSchedule,
+    accumulate_rewards: bool,
+    min_claim_amount: String,
+    plausibility_policy: PlausibilityPolicy,
+    start_limits: StartLimits,
+    max_concurrent_games: u8,
+    extension_config: ExtensionConfig,
+    pause_flags: PauseFlags,
+    timelock_delay: u64,
 }
 
 
@@ -105,6 +274,14 @@ impl MazeGameBuyerContract {
         
         game_costs.insert(1, to_yocto_u8(15).0);
         game_costs.insert(10, to_yocto_u8(14).0);
+        let mut difficulty_levels = UnorderedMap::new(b"difficulty_levels".to_vec());
+        for (difficulty, config) in default_difficulty_levels() {
+            difficulty_levels.insert(&difficulty, &config);
+        }
+        let mut achievements = UnorderedMap::new(b"achievements".to_vec());
+        for achievement in default_achievements() {
+            achievements.insert(&achievement.achievement_id.clone(), &achievement);
+        }
         Self {
             owner_id,
             cheddar_contract,
@@ -113,9 +290,57 @@ impl MazeGameBuyerContract {
             user_remaining_paid_games: UnorderedMap::new(b"paid_games".to_vec()),
             seed_id: 0u64,
             min_deposit: 1_000_000_000_000_000_000_000, // 0.001 NEAR
-            ongoing_games: UnorderedMap::new(b"ongoing_games".to_vec()),
+            ongoing_games: UnorderedMap::new(b"ongoing_games_by_id".to_vec()),
+            user_game_ids: LookupMap::new(b"user_game_ids".to_vec()),
+            last_game_id: 0,
+            max_concurrent_games: DEFAULT_MAX_CONCURRENT_GAMES,
             maze_minter_contract,
-            max_game_duration: 3 * MIN_MS,
+            difficulty_levels,
+            bond_policy: BondPolicy {
+                keep_on_forfeit: false,
+                keep_on_expiry: true,
+            },
+            reward_limits: RewardLimits::default(),
+            user_daily_rewards: LookupMap::new(b"user_daily_rewards".to_vec()),
+            daily_minted: DailyReward::default(),
+            reward_schedule: RewardSchedule::default(),
+            pending_rewards: LookupMap::new(b"pending_rewards".to_vec()),
+            accumulate_rewards: false,
+            min_claim_amount: 0,
+            reward_balances: LookupMap::new(b"reward_balances".to_vec()),
+            min_game_durations: UnorderedMap::new(b"min_game_durations".to_vec()),
+            plausibility_policy: PlausibilityPolicy::default(),
+            flagged_results: UnorderedMap::new(b"flagged_results".to_vec()),
+            bans: UnorderedMap::new(b"bans".to_vec()),
+            start_limits: StartLimits::default(),
+            game_starts: LookupMap::new(b"game_starts".to_vec()),
+            daily_challenges: LookupMap::new(b"daily_challenges".to_vec()),
+            daily_challenge_players: LookupSet::new(b"daily_challenge_players".to_vec()),
+            daily_challenge_rewards: vec![to_yocto_u8(50).0, to_yocto_u8(30).0, to_yocto_u8(20).0],
+            races: UnorderedMap::new(b"races".to_vec()),
+            last_race_id: 0,
+            race_config: RaceConfig::default(),
+            extension_config: ExtensionConfig::default(),
+            achievements,
+            user_achievements: LookupMap::new(b"user_achievements".to_vec()),
+            user_stats: LookupMap::new(b"user_stats".to_vec()),
+            badge_config: None,
+            roles: UnorderedSet::new(b"roles".to_vec()),
+            pending_owner_id: None,
+            pause_flags: PauseFlags::default(),
+            proposals: UnorderedMap::new(b"proposals".to_vec()),
+            last_proposal_id: 0,
+            timelock_delay: DAY_MS,
+            multisig_config: None,
+            multisig_requests: UnorderedMap::new(b"multisig_requests".to_vec()),
+            last_request_id: 0,
+            game_history: LookupMap::new(b"game_history".to_vec()),
+            leaderboards: LookupMap::new(b"leaderboards".to_vec()),
+            tournaments: UnorderedMap::new(b"tournaments".to_vec()),
+            tournament_players: LookupMap::new(b"tournament_players".to_vec()),
+            last_tournament_id: 0,
+            treasury: 0,
+            unpaid_cheddar: LookupMap::new(b"unpaid_cheddar".to_vec()),
         }
     }
 
@@ -123,35 +348,96 @@ impl MazeGameBuyerContract {
     #[init(ignore_state)]
     pub fn migrate() -> Self {
         // Load the existing state
-        let old_state: OldMazeGameBuyerContract = env::state_read().expect("Failed to read state");
+        let mut old_state: OldMazeGameBuyerContract = env::state_read().expect("Failed to read state");
 
-        let mut new_user_remaining_free_games = UnorderedMap::new(b"g"); // Use a new storage prefix
-        for (key, value) in old_state.user_remaining_free_games.iter() {
-            new_user_remaining_free_games.insert(&key, &value);
+        // Every account had at most one game, so each one gets its own id. Games took at least min_deposit
+        // and the contract kept it, so that is the bond they get back
+        let mut new_ongoing_games = UnorderedMap::new(b"ongoing_games_by_id".to_vec());
+        let mut user_game_ids = LookupMap::new(b"user_game_ids".to_vec());
+        let mut last_game_id: GameId = 0;
+        for (key, value) in old_state.ongoing_games.iter() {
+            last_game_id += 1;
+            new_ongoing_games.insert(&(key.clone(), last_game_id), &Game { bond: old_state.min_deposit, ..Game::from(value) });
+            user_game_ids.insert(&key, &vec![last_game_id]);
         }
-
-        let mut new_user_remaining_paid_games = UnorderedMap::new(b"g"); // Use a new storage prefix
-        for (key, value) in old_state.user_remaining_paid_games.iter() {
-            new_user_remaining_paid_games.insert(&key, &value);
+        // The games now live under the new prefix, so the old entries would only hold storage
+        old_state.ongoing_games.clear();
+
+        let mut difficulty_levels = UnorderedMap::new(b"difficulty_levels".to_vec());
+        for (difficulty, mut config) in default_difficulty_levels() {
+            // Every game used to last max_game_duration, which is what Normal games still do
+            if difficulty == Difficulty::Normal {
+                config.max_game_duration = old_state.max_game_duration;
+            }
+            difficulty_levels.insert(&difficulty, &config);
         }
-
-        let mut new_ongoing_games = UnorderedMap::new(b"g"); // Use a new storage prefix
-        for (key, value) in old_state.ongoing_games.iter() {
-            new_ongoing_games.insert(&key, &value);
+        let mut achievements = UnorderedMap::new(b"achievements".to_vec());
+        for achievement in default_achievements() {
+            achievements.insert(&achievement.achievement_id.clone(), &achievement);
         }
         // Create the new state, adding the default value for the new property
-        let new_state = Self {
+        let mut new_state = Self {
             owner_id: old_state.owner_id,
             cheddar_contract: old_state.cheddar_contract,
             game_costs: old_state.game_costs,
-            user_remaining_free_games: new_user_remaining_free_games,
-            user_remaining_paid_games: new_user_remaining_paid_games,
+            user_remaining_free_games: old_state.user_remaining_free_games,
+            user_remaining_paid_games: old_state.user_remaining_paid_games,
             seed_id: old_state.seed_id,
             min_deposit: old_state.min_deposit,
             ongoing_games: new_ongoing_games,
+            user_game_ids,
+            last_game_id,
+            max_concurrent_games: DEFAULT_MAX_CONCURRENT_GAMES,
             maze_minter_contract: old_state.maze_minter_contract,
-            max_game_duration: 3 * MIN_MS,
+            difficulty_levels,
+            bond_policy: BondPolicy {
+                keep_on_forfeit: false,
+                keep_on_expiry: true,
+            },
+            reward_limits: RewardLimits::default(),
+            user_daily_rewards: LookupMap::new(b"user_daily_rewards".to_vec()),
+            daily_minted: DailyReward::default(),
+            reward_schedule: RewardSchedule::default(),
+            pending_rewards: LookupMap::new(b"pending_rewards".to_vec()),
+            accumulate_rewards: false,
+            min_claim_amount: 0,
+            reward_balances: LookupMap::new(b"reward_balances".to_vec()),
+            min_game_durations: UnorderedMap::new(b"min_game_durations".to_vec()),
+            plausibility_policy: PlausibilityPolicy::default(),
+            flagged_results: UnorderedMap::new(b"flagged_results".to_vec()),
+            bans: UnorderedMap::new(b"bans".to_vec()),
+            start_limits: StartLimits::default(),
+            game_starts: LookupMap::new(b"game_starts".to_vec()),
+            daily_challenges: LookupMap::new(b"daily_challenges".to_vec()),
+            daily_challenge_players: LookupSet::new(b"daily_challenge_players".to_vec()),
+            daily_challenge_rewards: vec![to_yocto_u8(50).0, to_yocto_u8(30).0, to_yocto_u8(20).0],
+            races: UnorderedMap::new(b"races".to_vec()),
+            last_race_id: 0,
+            race_config: RaceConfig::default(),
+            extension_config: ExtensionConfig::default(),
+            achievements,
+            user_achievements: LookupMap::new(b"user_achievements".to_vec()),
+            user_stats: LookupMap::new(b"user_stats".to_vec()),
+            badge_config: None,
+            roles: UnorderedSet::new(b"roles".to_vec()),
+            pending_owner_id: None,
+            pause_flags: PauseFlags::default(),
+            proposals: UnorderedMap::new(b"proposals".to_vec()),
+            last_proposal_id: 0,
+            timelock_delay: DAY_MS,
+            multisig_config: None,
+            multisig_requests: UnorderedMap::new(b"multisig_requests".to_vec()),
+            last_request_id: 0,
+            game_history: LookupMap::new(b"game_history".to_vec()),
+            leaderboards: LookupMap::new(b"leaderboards".to_vec()),
+            tournaments: UnorderedMap::new(b"tournaments".to_vec()),
+            tournament_players: LookupMap::new(b"tournament_players".to_vec()),
+            last_tournament_id: 0,
+            treasury: 0,
+            unpaid_cheddar: LookupMap::new(b"unpaid_cheddar".to_vec()),
         };
+        // insert_game_cost used to append, so the deployed table may not be sorted
+        new_state.replace_game_costs(new_state.get_game_cost_table());
 
         // Save the new state
         env::state_write(&new_state);
@@ -167,37 +453,49 @@ impl MazeGameBuyerContract {
             game_costs: self.get_games_costs(),
             seed_id: self.seed_id,
             min_deposit: self.min_deposit.to_string(),
-            max_game_duration: 3 * MIN_MS,
+            max_game_duration: self.get_difficulty_config(&Difficulty::Normal).max_game_duration,
+            difficulty_levels: self.get_difficulty_levels(),
+            bond_policy: self.bond_policy.clone(),
+            reward_limits: self.reward_limits.clone(),
+            reward_schedule: self.reward_schedule.clone(),
+            accumulate_rewards: self.accumulate_rewards,
+            min_claim_amount: self.min_claim_amount.to_string(),
+            plausibility_policy: self.plausibility_policy.clone(),
+            start_limits: self.start_limits.clone(),
+            max_concurrent_games: self.max_concurrent_games,
+            extension_config: self.extension_config.clone(),
+            pause_flags: self.pause_flags.clone(),
+            timelock_delay: self.timelock_delay,
         }
     }
 
     pub fn get_games_costs(&self) -> Vec<[String; 2]> {
-        self.game_costs.iter().into_iter()
+        self.game_costs.iter()
         .map(|(key, value)| [key.to_string(), value.to_string()])
         .collect()
     }
 
-    // Ensure game_costs always has 1 as key, and at most 4 keys
-    pub fn insert_game_cost(&mut self, key: u8, value: U128) {
-        self.assert_only_owner();
-        assert!(key > 0, "Key must be greater than 0");
-        assert!(self.game_costs.len() < 4, "Cannot have more than 4 game costs");
-        self.game_costs.insert(key, value.0);
+    // Ensure game_costs always has 1 as key, and at most 4 keys.
+    // Pricing is timelocked: this proposes the change, execute_proposal applies it
+    pub fn insert_game_cost(&mut self, key: u8, value: U128) -> ProposalId {
+        self.assert_role(Role::PricingManager);
+        self.propose_config_change(ConfigChange::InsertGameCost { key, value })
     }
 
-    pub fn remove_game_cost(&mut self, key: u8) {
-        self.assert_only_owner();
-        assert!(self.game_costs.contains_key(&key), "Key does not exist");
-        self.game_costs.remove(&key);
+    pub fn remove_game_cost(&mut self, key: u8) -> ProposalId {
+        self.assert_role(Role::PricingManager);
+        self.propose_config_change(ConfigChange::RemoveGameCost { key })
     }
 
     pub fn get_cheddar_contract(&self) -> String {
         self.cheddar_contract.to_string()
     }
 
-    pub fn set_cheddar_contract(&mut self, cheddar_contract: AccountId) {
-        self.assert_only_owner();
-        self.cheddar_contract = cheddar_contract;
+    /// Timelocked: proposes the change, execute_proposal applies it.
+    /// With the multisig enabled this goes through a CheddarContract request instead
+    pub fn set_cheddar_contract(&mut self, cheddar_contract: AccountId) -> ProposalId {
+        self.assert_owner_acts_alone();
+        self.propose_config_change(ConfigChange::CheddarContract { account_id: cheddar_contract })
     }
 
     pub fn get_user_remaining_free_games(&self, account_id: &AccountId) -> GameAmount {
@@ -216,7 +514,7 @@ impl MazeGameBuyerContract {
     }
 
     pub fn give_free_game_to_user(&mut self, account_id: AccountId) {
-        self.assert_only_owner();
+        self.assert_role(Role::Admin);
         let day = env::block_timestamp_ms() / DAY_MS;
 
         let user_remaining_free_games = self.get_user_remaining_free_games(&account_id);
@@ -241,98 +539,240 @@ impl MazeGameBuyerContract {
         (self.get_user_remaining_free_games(account_id), self.get_user_remaining_paid_games(account_id))
     }
 
+    /// Starts a new game. Games are Normal unless another difficulty is requested.
+    /// Fails when the account already has max_concurrent_games ongoing
     #[payable]
-    pub fn get_seed_id(&mut self) -> SeedId {
+    pub fn get_seed_id(&mut self, difficulty: Option<Difficulty>) -> SeedId {
         let account_id = env::predecessor_account_id();
-        let user_ongoing_game = self.get_user_ongoing_game(account_id.clone());
-        log!("User ongoing game: {:?}", user_ongoing_game.is_some());
-        if user_ongoing_game.is_some() {
-            log!("User has ongoing game. Losing it");
-            self.lose_game();
-        }
+        self.assert_not_banned(&account_id);
+        let storage_usage = env::storage_usage();
+        self.record_game_start(&account_id);
 
-        let deposit = env::attached_deposit();
-        assert!(deposit.as_yoctonear() >= self.min_deposit, "Deposit must be at least {} yoctoNEAR", self.min_deposit);
+        let bond = self.assert_min_deposit();
 
+        let difficulty = difficulty.unwrap_or_default();
+        let cost = self.get_difficulty_config(&difficulty).cost_multiplier;
         let (remaining_free_games, remaining_paid_games) = self.get_user_remaining_games(&account_id);
-        assert!(remaining_free_games > 0 || remaining_paid_games > 0, "No games remaining for the user");
+        assert!(remaining_free_games as u32 + remaining_paid_games as u32 >= cost as u32, "No games remaining for the user");
 
-        self.decrease_game(account_id.clone());
-        self.seed_id += 1;
-        self.ongoing_games.insert(&account_id, &Game {
-            seed_id: self.seed_id,
-            start_time: env::block_timestamp_ms(),
-            is_ending_game: false
-        });
-        self.seed_id
+        self.decrease_game(account_id.clone(), cost);
+        let seed_id = self.next_seed_id();
+        self.start_game(&account_id, Game::new(seed_id, difficulty, bond), storage_usage);
+        seed_id
     }
 
-    fn lose_game(&mut self) {
+    /// Gives up an ongoing game of the caller, the latest one unless a game id is given. It is recorded as forfeited
+    pub fn forfeit_game(&mut self, game_id: Option<GameId>) {
         let account_id = env::predecessor_account_id();
-        self.internal_end_game(account_id, U128(0), None);
+        let (game_id, ongoing_game) = self.expect_ongoing_game(&account_id, game_id);
+        self.settle_game(account_id, game_id, ongoing_game, GameOutcome::Forfeited, U128(0), None);
+    }
+
+    pub fn set_max_concurrent_games(&mut self, max_concurrent_games: u8) {
+        self.assert_role(Role::Admin);
+        require!(max_concurrent_games > 0, "Max concurrent games must be greater than 0");
+        self.max_concurrent_games = max_concurrent_games;
+    }
+
+    /// Checks the ongoing games from index `from` to `from + limit` and records the ones that ran out of
+    /// time as expired. Anyone can call it. Returns the amount of games settled.
+    /// Settling a game moves the last ongoing game to its index, so that range is worth checking again
+    /// while games are being settled
+    pub fn settle_expired_games(&mut self, from: u64, limit: u64) -> u64 {
+        let now = get_now_ms();
+        let keys = self.ongoing_games.keys_as_vector();
+        let values = self.ongoing_games.values_as_vector();
+        let end = from.saturating_add(limit).min(keys.len());
+        let expired_games: Vec<((AccountId, GameId), Game)> = (from.min(end)..end)
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .filter(|(_, game)| now - game.start_time >= self.get_game_duration(game))
+            .collect();
+        let settled = expired_games.len() as u64;
+        for ((account_id, game_id), game) in expired_games {
+            log!("Game {} of {} expired", game_id, account_id);
+            self.settle_game(account_id, game_id, game, GameOutcome::Expired, U128(0), None);
+        }
+        settled
     }
 
 
-    fn decrease_game(&mut self, account_id: AccountId) {
+    // Free games are spent first
+    fn decrease_game(&mut self, account_id: AccountId, amount: GameAmount) {
         let (remaining_free_games, remaining_paid_games) = self.get_user_remaining_games(&account_id);
-        if remaining_free_games > 0 {
-            log!("Decreasing free game for {}", account_id);
+        let free_games_used = remaining_free_games.min(amount);
+        if free_games_used > 0 {
+            log!("Decreasing {} free games for {}", free_games_used, account_id);
             self.user_remaining_free_games.insert(&account_id, &FreeGameInfo {
                 day: get_today_day(),
-                amount: remaining_free_games - 1,
+                amount: remaining_free_games - free_games_used,
             });
-        } else {
-            let new_remaining_paid_games = remaining_paid_games - 1;
+        }
+        if amount > free_games_used {
+            let new_remaining_paid_games = remaining_paid_games - (amount - free_games_used);
             self.user_remaining_paid_games.insert(&account_id, &new_remaining_paid_games);
         }
     }
 
+    /// Latest ongoing game of the account
     pub fn get_user_ongoing_game(&self, account_id: AccountId) -> Option<GameJson> {
-        let ongoing_game = self.ongoing_games.get(&account_id).unwrap_or(Game::default());
-        let now = get_now_ms();
-        log!("Now: {}", get_now_ms());
-        log!("Start time: {}", ongoing_game.start_time);
-        assert!(now >= ongoing_game.start_time, "Start time is in the future");
-        if get_now_ms() - ongoing_game.start_time >= self.max_game_duration {
-            return None
-        }; 
-        Some(GameJson {
+        self.get_user_ongoing_games(account_id).pop()
+    }
+
+    /// Ongoing games of the account, oldest first
+    pub fn get_user_ongoing_games(&self, account_id: AccountId) -> Vec<GameJson> {
+        self.get_ongoing_game_ids(&account_id).into_iter()
+            .filter_map(|game_id| self.get_ongoing_game(&account_id, game_id).map(|game| GameJson::from_game(game_id, &game, self.get_game_duration(&game))))
+            .collect()
+    }
+
+    /// Settles the game with the result reported by the game server. The reward is computed
+    /// by the reward schedule, from the time it took and the collected items.
+    /// Without a game id the latest ongoing game of the account is settled
+    pub fn end_game(&mut self, account_id: AccountId, game_id: Option<GameId>, result: GameResult, referral: Option<AccountId>) -> Promise {
+        self.assert_role(Role::GameOperator);
+        let (game_id, ongoing_game) = self.expect_ongoing_game(&account_id, game_id);
+        let reward = if result.won {
+            self.compute_reward(&ongoing_game, get_now_ms() - ongoing_game.start_time, result.collected_items)
+        } else {
+            0
+        };
+        self.internal_end_game(account_id, game_id, ongoing_game, U128(reward), referral)
+    }
+
+    fn internal_end_game(&mut self, account_id: AccountId, game_id: GameId, ongoing_game: Game, amount: U128, referral: Option<AccountId>) -> Promise {
+        let amount = if amount.0 > 0 && self.is_banned(&account_id) {
+            log!("{} is banned. Reward of {} not given", account_id, amount.0);
+            U128(0)
+        } else {
+            amount
+        };
+        let outcome = if amount.0 > 0 { GameOutcome::Won } else { GameOutcome::Lost };
+        self.settle_game(account_id.clone(), game_id, ongoing_game, outcome, amount, referral)
+            .unwrap_or_else(|| Promise::new(account_id))
+    }
+
+    // Removes the game and keeps track of its outcome. Returns the mint when there is a reward
+    fn settle_game(&mut self, account_id: AccountId, game_id: GameId, ongoing_game: Game, outcome: GameOutcome, amount: U128, referral: Option<AccountId>) -> Option<Promise> {
+        self.assert_settlement_not_paused();
+        let storage_usage = env::storage_usage();
+        if outcome == GameOutcome::Won {
+            self.check_plausible_win(&account_id, &ongoing_game);
+            self.record_achievements(&account_id, get_now_ms() - ongoing_game.start_time);
+        }
+        if ongoing_game.mints_reward() {
+            let max_reward = self.get_difficulty_config(&ongoing_game.difficulty).max_reward;
+            require!(amount.0 <= max_reward, format!("Reward can't be more than {} for {:?} games", max_reward, ongoing_game.difficulty));
+            if amount.0 > 0 {
+                self.consume_reward_limits(&account_id, amount.0);
+            }
+        }
+        self.remove_game(&account_id, game_id);
+        let end_time = get_now_ms();
+        self.archive_game(&account_id, GameRecord {
             seed_id: ongoing_game.seed_id,
             start_time: ongoing_game.start_time,
-        })
+            end_time,
+            outcome,
+            reward: amount.0,
+            referral: referral.clone(),
+        });
+        let entry = LeaderboardEntry {
+            account_id: account_id.clone(),
+            seed_id: ongoing_game.seed_id,
+            reward: amount.0,
+            duration: end_time - ongoing_game.start_time,
+            end_time,
+        };
+        let promise = self.record_game_result(account_id.clone(), &ongoing_game, entry, amount, referral);
+        self.refund_bond(&account_id, &ongoing_game, &outcome, storage_usage);
+        promise
     }
 
-    pub fn end_game(&mut self, account_id: AccountId, amount: U128, referral: Option<AccountId>) -> Promise {
-        self.assert_only_owner();
-        self.internal_end_game(account_id, amount, referral)
+    fn record_game_result(&mut self, account_id: AccountId, ongoing_game: &Game, entry: LeaderboardEntry, amount: U128, referral: Option<AccountId>) -> Option<Promise> {
+        // Tournament prizes come from the pool, so those wins are ranked but nothing is minted
+        if let Some(tournament_id) = ongoing_game.tournament_id {
+            if amount.0 > 0 {
+                self.record_tournament_result(tournament_id, entry);
+            }
+            return None;
+        }
+        // The pot of a race was staked by its players, so the winner is paid from it
+        if let Some(race_id) = ongoing_game.race_id {
+            return self.record_race_result(race_id, &account_id, amount.0 > 0);
+        }
+        // Same for the daily challenge, whose rewards are claimed once the day is over
+        if let Some(day) = ongoing_game.challenge_day {
+            if amount.0 > 0 {
+                self.record_daily_challenge_result(day, entry);
+            }
+            return None;
+        }
+        if amount.0 > 0 {
+            self.record_leaderboards(entry);
+        }
+
+        if amount > U128(0) && self.accumulate_rewards {
+            self.credit_reward(&account_id, amount.0, referral);
+            None
+        } else if amount > U128(0) {
+            Some(self.mint_reward(account_id, amount.0, referral))
+        } else {
+            None
+        }
     }
 
-    fn internal_end_game(&mut self, account_id: AccountId, amount: U128, referral: Option<AccountId>) -> Promise {
-        let ongoing_game = self.get_user_ongoing_game(account_id.clone());
-        assert!(ongoing_game.is_some(), "No ongoing game for the user");
-        self.ongoing_games.remove(&account_id);
+    /// Finished games of the account, oldest first. Only the last GAME_HISTORY_SIZE games are kept
+    pub fn get_user_game_history(&self, account_id: AccountId, from: u64, limit: u64) -> Vec<GameRecordJson> {
+        let history = self.game_history.get(&account_id).unwrap_or_default();
+        history.iter_from(from as usize)
+            .take(limit as usize)
+            .map(GameRecordJson::from)
+            .collect()
+    }
 
-        if amount > U128(0) {
-            ext_maze_minter::ext(self.maze_minter_contract.clone())
-                .with_static_gas(Gas::from_tgas(30))
-                .with_attached_deposit(NearToken::from_yoctonear(1))
-                .mint(account_id, amount, referral)
-        } else {
-            Promise::new(account_id)
+    /// Best wins of the current day, week or ever, ranked by reward or by completion time
+    pub fn get_leaderboard(&self, period: LeaderboardPeriod, kind: LeaderboardKind, from: u64, limit: u64) -> Vec<LeaderboardEntryJson> {
+        let leaderboard = self.leaderboards.get(&(period, kind)).unwrap_or_default();
+        if leaderboard.period_index != period.index(get_today_day()) {
+            return vec![];
         }
+        leaderboard.entries.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(LeaderboardEntryJson::from)
+            .collect()
+    }
+
+    /// Timelocked: proposes the change, execute_proposal applies it.
+    /// With the multisig enabled this goes through a MazeMinterContract request instead
+    pub fn set_maze_minter_contract(&mut self, maze_minter_contract: AccountId) -> ProposalId {
+        self.assert_owner_acts_alone();
+        self.propose_config_change(ConfigChange::MazeMinterContract { account_id: maze_minter_contract })
+    }
+
+    /// Settling a game on-chain needs the maze generator of the client ported to this contract, so both
+    /// build the same maze from a seed. Until it is, wins are only settled by end_game
+    pub fn validate_ongoing_game(&self) -> bool {
+        false
     }
 
-    pub fn set_maze_minter_contract(&mut self, maze_minter_contract: AccountId) {
-        self.assert_only_owner();
-        self.maze_minter_contract = maze_minter_contract;
+    pub fn get_difficulty_levels(&self) -> Vec<DifficultyConfigJson> {
+        self.difficulty_levels.iter()
+            .map(|(difficulty, config)| DifficultyConfigJson::from_config(difficulty, &config))
+            .collect()
     }
 
-    pub fn validate_ongoing_game(self) -> bool{
-        return false
+    /// Timelocked: the cost multiplier is pricing, so the level is proposed and execute_proposal applies it
+    pub fn set_difficulty_level(&mut self, difficulty: Difficulty, cost_multiplier: GameAmount, game_duration_seconds: u64, max_reward: U128) -> ProposalId {
+        self.assert_role(Role::PricingManager);
+        self.propose_config_change(ConfigChange::DifficultyLevel {
+            level: DifficultyLevel { difficulty, cost_multiplier, game_duration_seconds, max_reward },
+        })
     }
 
+    // A failed mint is kept in the pending rewards ledger, so the player can claim it later
     #[private]
-    pub fn on_mint_callback(&self) -> String {
+    pub fn on_mint_callback(&mut self, account_id: AccountId, amount: U128, referral: Option<AccountId>) -> String {
         match env::promise_result(0) {
             near_sdk::PromiseResult::Successful(_) => {
                 env::log_str("Minting successful");
@@ -340,14 +780,24 @@ impl MazeGameBuyerContract {
             }
             near_sdk::PromiseResult::Failed => {
                 env::log_str("Minting failed");
+                self.add_pending_reward(&account_id, PendingReward {
+                    amount: amount.0,
+                    referral,
+                });
                 "Minting failed".to_string()
             }
         }
     }
 
+    pub fn set_bond_policy(&mut self, bond_policy: BondPolicy) {
+        self.assert_role(Role::Admin);
+        self.bond_policy = bond_policy;
+    }
+
+    /// Sets the duration of the Normal level, which tournament, daily challenge and race games are played on
     pub fn set_max_game_duration(&mut self, game_duration_seconds: u64) {
-        self.assert_only_owner();
-        self.max_game_duration = game_duration_seconds * 1000;
+        self.assert_role(Role::Admin);
+        self.set_game_duration(Difficulty::Normal, game_duration_seconds * 1000);
     }
 }
 
@@ -359,7 +809,8 @@ impl MazeGameBuyerContract {
 mod tests {
     use std::str::FromStr;
 
-    use near_sdk::{test_utils::{accounts, VMContextBuilder}, testing_env, NearToken};
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_sdk::{test_utils::{accounts, VMContextBuilder}, testing_env, NearToken, PromiseOrValue};
 
     use super::*;
 
@@ -382,14 +833,24 @@ mod tests {
         assert_eq!(contract.get_games_costs(), game_costs);
     }
 
+    fn execute_after_delay(context: &mut VMContextBuilder, contract: &mut MazeGameBuyerContract, proposal_id: ProposalId) {
+        let executable_at = contract.get_pending_proposals(0, 10).iter()
+            .find(|proposal| proposal.proposal_id == proposal_id)
+            .map_or(2 * DAY_MS, |proposal| proposal.executable_at);
+        testing_env!(context.block_timestamp(executable_at * MS_TO_NANOS).build());
+        contract.execute_proposal(proposal_id);
+    }
+
     #[test]
     fn set_then_get_game_costs() {
-        let (_, mut contract) = setup_contract();
-        contract.insert_game_cost(1, to_yocto_u8(20));
+        let (mut context, mut contract) = setup_contract();
+        let proposal_id = contract.insert_game_cost(1, to_yocto_u8(20));
+        execute_after_delay(&mut context, &mut contract, proposal_id);
         let new_game_costs_1 = [["1".to_string(), to_yocto_u8(20).0.to_string()], ["10".to_string(), to_yocto_u8(14).0.to_string()]];
         assert_eq!(contract.get_games_costs(), new_game_costs_1);
 
-        contract.remove_game_cost(10);
+        let proposal_id = contract.remove_game_cost(10);
+        execute_after_delay(&mut context, &mut contract, proposal_id);
         let new_game_costs_2 = [["1".to_string(), to_yocto_u8(20).0.to_string()]];
         assert_eq!(contract.get_games_costs(), new_game_costs_2);
     }
@@ -404,9 +865,10 @@ mod tests {
 
     #[test]
     fn set_then_get_cheddar_contract() {
-        let (_, mut contract) = setup_contract();
+        let (mut context, mut contract) = setup_contract();
         let cheddar_contract: AccountId = AccountId::from_str("token-v2.cheddar.near").unwrap();
-        contract.set_cheddar_contract(cheddar_contract.clone());
+        let proposal_id = contract.set_cheddar_contract(cheddar_contract.clone());
+        execute_after_delay(&mut context, &mut contract, proposal_id);
         assert_eq!(contract.get_cheddar_contract(), cheddar_contract);
     }
 
@@ -455,8 +917,10 @@ mod tests {
         context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
         testing_env!(context.build());
         let user = accounts(0);
-        assert_eq!(contract.get_seed_id(), 1);
+        let seed_id = contract.get_seed_id(None);
+        assert_eq!(contract.get_user_ongoing_game(user.clone()).unwrap().seed_id, seed_id);
         assert_eq!(contract.get_user_remaining_games(&user), (4, 0));
+        assert_eq!(contract.get_contract_state().seed_id, 1);
     }
 
     #[test]
@@ -465,10 +929,10 @@ mod tests {
         context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
         testing_env!(context.build());
         let user = accounts(0);
-        assert_eq!(contract.get_seed_id(), 1);
+        let first_seed_id = contract.get_seed_id(None);
         assert_eq!(contract.get_user_remaining_games(&user), (4, 0));
 
-        assert_eq!(contract.get_seed_id(), 2);
+        assert_ne!(contract.get_seed_id(None), first_seed_id);
         assert_eq!(contract.get_user_remaining_games(&user), (3, 0));
     }
 
@@ -478,14 +942,25 @@ mod tests {
         context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
         testing_env!(context.build());
         let user = accounts(0);
-        assert_eq!(contract.get_seed_id(), 1);
+        let first_seed_id = contract.get_seed_id(None);
         assert_eq!(contract.get_user_remaining_games(&user), (4, 0));
 
-        contract.end_game(user.clone(), U128(1_000_000_000_000_000_000_000_000), None);
-        assert_eq!(contract.get_seed_id(), 2);
+        contract.end_game(user.clone(), None, GameResult { won: true, collected_items: 0 }, None);
+        assert_ne!(contract.get_seed_id(None), first_seed_id);
         assert_eq!(contract.get_user_remaining_games(&user), (3, 0));
     }
 
+    #[test]
+    fn seeds_depend_on_block_randomness() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.random_seed([1; 32]).build());
+        let seed_id = contract.next_seed_id();
+        contract.seed_id = 0;
+        testing_env!(context.random_seed([2; 32]).build());
+        assert_ne!(contract.next_seed_id(), seed_id);
+        assert_eq!(contract.get_contract_state().seed_id, 1);
+    }
+
     #[test]
     fn test_ongoing_game() {
         let (mut context, mut contract) = setup_contract();
@@ -494,17 +969,18 @@ mod tests {
         let user = accounts(0);
         let ongoing_game = contract.get_user_ongoing_game(user.clone());
         assert!(ongoing_game.is_none());
-        contract.get_seed_id();
+        let seed_id = contract.get_seed_id(None);
         let ongoing_game = contract.get_user_ongoing_game(user.clone());
         assert!(ongoing_game.is_some());
-        assert!(ongoing_game.unwrap().seed_id == 1);
+        assert!(ongoing_game.unwrap().seed_id == seed_id);
     }
 
     #[test]
     fn set_then_get_maze_minter_contract() {
-        let (_, mut contract) = setup_contract();
+        let (mut context, mut contract) = setup_contract();
         let new_maze_minter_contract = AccountId::from_str("new.maze.minter.near").unwrap();
-        contract.set_maze_minter_contract(new_maze_minter_contract.clone());
+        let proposal_id = contract.set_maze_minter_contract(new_maze_minter_contract.clone());
+        execute_after_delay(&mut context, &mut contract, proposal_id);
         let contract_state = contract.get_contract_state();
         assert!(contract_state.maze_minter_contract == new_maze_minter_contract);
     }
@@ -516,11 +992,11 @@ mod tests {
         testing_env!(context.build());
         let user = accounts(0);
 
-        assert_eq!(contract.get_seed_id(), 1);
+        let seed_id = contract.get_seed_id(None);
         let ongoing_game = contract.get_user_ongoing_game(user.clone());
         assert!(ongoing_game.is_some());
         let unwraped_ongoing_game = ongoing_game.unwrap();
-        assert!(unwraped_ongoing_game.seed_id == 1);
+        assert!(unwraped_ongoing_game.seed_id == seed_id);
         assert!(unwraped_ongoing_game.start_time > 0);
 
         let state = contract.get_contract_state();
@@ -531,4 +1007,1438 @@ mod tests {
         assert!(ongoing_game.is_none());
     }
 
+    #[test]
+    fn finished_games_are_archived() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        let won_seed_id = contract.get_seed_id(None);
+        contract.end_game(user.clone(), None, GameResult { won: true, collected_items: 0 }, Some(accounts(1)));
+        let forfeited_seed_id = contract.get_seed_id(None);
+        contract.forfeit_game(None);
+
+        let history = contract.get_user_game_history(user.clone(), 0, 10);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].seed_id, won_seed_id);
+        assert_eq!(history[0].outcome, GameOutcome::Won);
+        assert_eq!(history[0].reward, to_yocto_u8(10).0.to_string());
+        assert_eq!(history[0].referral, Some(accounts(1).to_string()));
+        assert_eq!(history[1].seed_id, forfeited_seed_id);
+        assert_eq!(history[1].outcome, GameOutcome::Forfeited);
+
+        let page = contract.get_user_game_history(user, 1, 10);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].seed_id, forfeited_seed_id);
+    }
+
+    #[test]
+    fn game_history_keeps_last_games() {
+        let mut history = GameHistory::default();
+        for seed_id in 0..(history::GAME_HISTORY_SIZE as u64 + 3) {
+            history.push(GameRecord {
+                seed_id,
+                start_time: 0,
+                end_time: 0,
+                outcome: GameOutcome::Lost,
+                reward: 0,
+                referral: None,
+            });
+        }
+        let seeds: Vec<SeedId> = history.iter_from(0).map(|record| record.seed_id).collect();
+        assert_eq!(seeds.len(), history::GAME_HISTORY_SIZE);
+        assert_eq!(seeds[0], 3);
+        assert_eq!(*seeds.last().unwrap(), history::GAME_HISTORY_SIZE as u64 + 2);
+    }
+
+    #[test]
+    fn wins_are_ranked_in_leaderboards() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        contract.set_reward_schedule(RewardSchedule {
+            min_time_share: 0,
+            item_reward: U128(to_yocto_u8(1).0 / 10),
+            max_items: 10,
+        });
+        // Later starts are a bit faster, but items make the difference
+        for (index, collected_items) in [(0, 0u16), (1, 3), (2, 1)] {
+            testing_env!(context.predecessor_account_id(accounts(index)).block_timestamp((DAY_MS + index as u64 * 1000) * MS_TO_NANOS).build());
+            contract.get_seed_id(None);
+            testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 10_000) * MS_TO_NANOS).build());
+            contract.end_game(accounts(index), None, GameResult { won: true, collected_items }, None);
+        }
+
+        let by_reward = contract.get_leaderboard(LeaderboardPeriod::Daily, LeaderboardKind::Reward, 0, 10);
+        let accounts_by_reward: Vec<String> = by_reward.iter().map(|entry| entry.account_id.clone()).collect();
+        assert_eq!(accounts_by_reward, vec![accounts(1).to_string(), accounts(2).to_string(), accounts(0).to_string()]);
+
+        // Accounts started one second apart and all finished at the same time
+        let by_time = contract.get_leaderboard(LeaderboardPeriod::AllTime, LeaderboardKind::CompletionTime, 1, 10);
+        assert_eq!(by_time.len(), 2);
+        assert_eq!(by_time[0].account_id, accounts(1).to_string());
+        assert_eq!(by_time[0].duration, 9_000);
+
+        testing_env!(context.block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        assert!(contract.get_leaderboard(LeaderboardPeriod::Daily, LeaderboardKind::Reward, 0, 10).is_empty());
+        assert_eq!(contract.get_leaderboard(LeaderboardPeriod::AllTime, LeaderboardKind::Reward, 0, 10).len(), 3);
+    }
+
+    fn setup_tournament(context: &mut VMContextBuilder, contract: &mut MazeGameBuyerContract) -> TournamentId {
+        let tournament_id = contract.create_tournament(to_yocto_u8(5), DAY_MS, DAY_MS + 10 * MIN_MS, vec![7_000, 3_000]);
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        for index in 1..4 {
+            let transfer_msg = format!("{{\"action\":\"enter_tournament\",\"tournament_id\":{}}}", tournament_id);
+            contract.ft_on_transfer(accounts(index), to_yocto_u8(5), transfer_msg);
+        }
+        tournament_id
+    }
+
+    #[test]
+    fn tournament_ranks_players_and_finalizes() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = setup_tournament(&mut context, &mut contract);
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        for index in 1..4 {
+            testing_env!(context.predecessor_account_id(accounts(index)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+            contract.start_tournament_game(tournament_id);
+            testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + index as u64 * 1000) * MS_TO_NANOS).build());
+            contract.end_game(accounts(index), None, GameResult { won: true, collected_items: 0 }, None);
+        }
+
+        let tournament = contract.get_tournament(tournament_id).unwrap();
+        assert_eq!(tournament.pool, to_yocto_u8(15).0.to_string());
+        let ranking = contract.get_tournament_ranking(tournament_id, 0, 10);
+        assert_eq!(ranking[0].account_id, accounts(1).to_string());
+        assert_eq!(contract.get_user_tournament(tournament_id, accounts(2)).best_duration, Some(2000));
+        // Tournament wins are paid from the pool, not on the regular leaderboards
+        assert!(contract.get_leaderboard(LeaderboardPeriod::AllTime, LeaderboardKind::Reward, 0, 10).is_empty());
+
+        testing_env!(context.block_timestamp((DAY_MS + 10 * MIN_MS) * MS_TO_NANOS).build());
+        contract.finalize_tournament(tournament_id);
+        assert!(contract.get_tournament(tournament_id).unwrap().finalized);
+    }
+
+    #[test]
+    fn late_tournament_players_are_timed_from_the_tournament_start() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = setup_tournament(&mut context, &mut contract);
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+        contract.start_tournament_game(tournament_id);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 3000) * MS_TO_NANOS).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+
+        // Knows the seed from the first game, so the maze is solved almost at once
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp((DAY_MS + 5000) * MS_TO_NANOS).build());
+        contract.start_tournament_game(tournament_id);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 5100) * MS_TO_NANOS).build());
+        contract.end_game(accounts(2), None, GameResult { won: true, collected_items: 0 }, None);
+
+        let ranking = contract.get_tournament_ranking(tournament_id, 0, 10);
+        assert_eq!(ranking[0].account_id, accounts(1).to_string());
+        assert_eq!(ranking[1].account_id, accounts(2).to_string());
+        assert_eq!(contract.get_user_tournament(tournament_id, accounts(2)).best_duration, Some(5100));
+    }
+
+    #[test]
+    #[should_panic(expected = "No tournament attempts remaining")]
+    fn tournament_attempts_are_paid() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = setup_tournament(&mut context, &mut contract);
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.start_tournament_game(tournament_id);
+        contract.start_tournament_game(tournament_id);
+    }
+
+    #[test]
+    fn max_game_duration_sets_the_normal_level() {
+        let (mut context, mut contract) = setup_contract();
+        contract.set_max_game_duration(60);
+        let normal = contract.get_difficulty_levels().into_iter().find(|level| level.difficulty == Difficulty::Normal).unwrap();
+        assert_eq!(normal.max_game_duration, MIN_MS);
+        assert_eq!(contract.get_contract_state().max_game_duration, MIN_MS);
+
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+        testing_env!(context.block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        assert!(contract.get_user_ongoing_game(accounts(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament must pay between 1 and 10 ranks")]
+    fn tournament_payouts_fit_in_one_call() {
+        let (_, mut contract) = setup_contract();
+        contract.create_tournament(to_yocto_u8(5), DAY_MS, DAY_MS + 10 * MIN_MS, vec![500; 11]);
+    }
+
+    #[test]
+    fn tournament_seed_is_hidden_until_it_starts() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = contract.create_tournament(to_yocto_u8(5), DAY_MS + MIN_MS, DAY_MS + 10 * MIN_MS, vec![10_000]);
+        assert_eq!(contract.get_tournament(tournament_id).unwrap().seed_id, None);
+
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(5), format!("{{\"action\":\"enter_tournament\",\"tournament_id\":{}}}", tournament_id));
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        let seed_id = contract.start_tournament_game(tournament_id);
+        assert_eq!(contract.get_tournament(tournament_id).unwrap().seed_id, Some(seed_id));
+    }
+
+    #[test]
+    fn tournament_leftover_goes_to_the_treasury() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = setup_tournament(&mut context, &mut contract);
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+        contract.start_tournament_game(tournament_id);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 1000) * MS_TO_NANOS).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+
+        // Only the first rank was reached, so its 70% is paid and the 30% of the second rank is left
+        testing_env!(context.block_timestamp((DAY_MS + 10 * MIN_MS) * MS_TO_NANOS).build());
+        contract.finalize_tournament(tournament_id);
+        assert_eq!(contract.get_treasury().0, to_yocto_u8(15).0 * 3 / 10);
+
+        contract.withdraw_treasury(accounts(0), U128(to_yocto_u8(15).0 * 3 / 10));
+        assert_eq!(contract.get_treasury().0, 0);
+    }
+
+    #[test]
+    fn failed_transfers_can_be_claimed() {
+        let (_, mut contract) = setup_contract();
+        let mut context = VMContextBuilder::new();
+        context.current_account_id(accounts(0)).predecessor_account_id(accounts(0)).block_timestamp(DAY_MS * MS_TO_NANOS);
+        testing_env!(context.build(), near_sdk::test_vm_config(), near_sdk::RuntimeFeesConfig::test(), Default::default(), vec![near_sdk::PromiseResult::Failed]);
+        assert!(!contract.on_cheddar_transfer(accounts(1), U128(1_000)));
+        assert!(!contract.on_cheddar_transfer(accounts(1), U128(500)));
+        assert_eq!(contract.get_user_unpaid_cheddar(accounts(1)).0, 1_500);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_unpaid_cheddar();
+        assert_eq!(contract.get_user_unpaid_cheddar(accounts(1)).0, 0);
+    }
+
+    #[test]
+    fn forfeit_game() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        contract.get_seed_id(None);
+        contract.forfeit_game(None);
+        assert!(contract.get_user_ongoing_game(user.clone()).is_none());
+        assert_eq!(contract.get_user_game_history(user, 0, 10)[0].outcome, GameOutcome::Forfeited);
+    }
+
+    #[test]
+    fn settle_expired_games() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        for index in 0..3 {
+            testing_env!(context.predecessor_account_id(accounts(index)).build());
+            contract.get_seed_id(None);
+        }
+        assert_eq!(contract.settle_expired_games(0, 10), 0);
+
+        testing_env!(context.predecessor_account_id(accounts(4)).block_timestamp((DAY_MS + 3 * MIN_MS) * MS_TO_NANOS).build());
+        // Only the games in the range are checked
+        assert_eq!(contract.settle_expired_games(2, 5), 1);
+        assert_eq!(contract.settle_expired_games(0, 1), 1);
+        assert_eq!(contract.settle_expired_games(5, 10), 0);
+        assert_eq!(contract.settle_expired_games(0, 10), 1);
+        assert!(contract.ongoing_games.is_empty());
+        assert_eq!(contract.get_user_game_history(accounts(2), 0, 10)[0].outcome, GameOutcome::Expired);
+    }
+
+    fn transfers_to(account_id: &AccountId) -> Vec<u128> {
+        near_sdk::test_utils::get_created_receipts().into_iter()
+            .filter(|receipt| &receipt.receiver_id == account_id)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::Transfer { deposit, .. } => Some(deposit.as_yoctonear()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bond_is_refunded_when_game_ends() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(10_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        let storage_usage = env::storage_usage();
+        contract.get_seed_id(None);
+        assert_eq!(contract.get_user_ongoing_game(user.clone()).unwrap().bond, "10000000000000000000000");
+
+        // What the game leaves in storage, like its history entry, is kept out of the refund
+        contract.end_game(user.clone(), None, GameResult { won: false, collected_items: 0 }, None);
+        let storage_cost = (env::storage_usage() - storage_usage) as u128 * env::storage_byte_cost().as_yoctonear();
+        assert!(storage_cost > 0);
+        assert_eq!(transfers_to(&user), vec![10_000_000_000_000_000_000_000 - storage_cost]);
+    }
+
+    #[test]
+    fn bond_is_kept_on_expiry() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        contract.get_seed_id(None);
+
+        testing_env!(context.block_timestamp((DAY_MS + 3 * MIN_MS) * MS_TO_NANOS).build());
+        contract.settle_expired_games(0, 10);
+        assert!(transfers_to(&user).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Reward exceeds the daily reward limit of the user. Remaining today: 5000000000000000000000000")]
+    fn user_daily_reward_limit() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        contract.set_reward_limits(RewardLimits {
+            max_per_game: to_yocto_u8(10),
+            max_per_user_daily: to_yocto_u8(15),
+            daily_mint_budget: to_yocto_u8(100),
+        });
+        let win = GameResult { won: true, collected_items: 0 };
+        contract.get_seed_id(None);
+        contract.end_game(user.clone(), None, win.clone(), None);
+        assert_eq!(contract.get_user_remaining_daily_reward(user.clone()), to_yocto_u8(5));
+        assert_eq!(contract.get_remaining_mint_budget(), to_yocto_u8(90));
+        contract.get_seed_id(None);
+        contract.end_game(user, None, win, None);
+    }
+
+    #[test]
+    fn daily_mint_budget_resets_every_day() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_reward_limits(RewardLimits {
+            max_per_game: to_yocto_u8(10),
+            max_per_user_daily: to_yocto_u8(10),
+            daily_mint_budget: to_yocto_u8(15),
+        });
+        for index in 0..2 {
+            testing_env!(context.predecessor_account_id(accounts(index)).build());
+            contract.get_seed_id(None);
+        }
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        assert_eq!(contract.get_user_remaining_daily_reward(accounts(1)), to_yocto_u8(5));
+
+        testing_env!(context.block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        assert_eq!(contract.get_remaining_mint_budget(), to_yocto_u8(15));
+    }
+
+    #[test]
+    fn raising_reward_limits_is_timelocked() {
+        let (mut context, mut contract) = setup_contract();
+        let lower = RewardLimits {
+            max_per_game: to_yocto_u8(10),
+            max_per_user_daily: to_yocto_u8(50),
+            daily_mint_budget: to_yocto_u8(200),
+        };
+        assert!(contract.set_reward_limits(lower).is_none());
+        assert_eq!(contract.get_reward_limits().max_per_game, to_yocto_u8(10));
+
+        let proposal_id = contract.set_reward_limits(RewardLimits {
+            max_per_game: to_yocto_u8(20),
+            max_per_user_daily: to_yocto_u8(50),
+            daily_mint_budget: to_yocto_u8(200),
+        }).expect("Raising a limit must be proposed");
+        assert_eq!(contract.get_reward_limits().max_per_game, to_yocto_u8(10));
+        // A patch raising a limit is held back as well
+        assert!(contract.update_config(config::ConfigPatch {
+            reward_limits: Some(RewardLimits {
+                max_per_game: to_yocto_u8(10),
+                max_per_user_daily: to_yocto_u8(60),
+                daily_mint_budget: to_yocto_u8(200),
+            }),
+            ..Default::default()
+        }).is_some());
+        assert_eq!(contract.get_reward_limits().max_per_user_daily, to_yocto_u8(50));
+
+        execute_after_delay(&mut context, &mut contract, proposal_id);
+        assert_eq!(contract.get_reward_limits().max_per_game, to_yocto_u8(20));
+    }
+
+    fn mint_callback_with_result(contract: &mut MazeGameBuyerContract, result: near_sdk::PromiseResult) -> String {
+        let mut context = VMContextBuilder::new();
+        context.current_account_id(accounts(0)).predecessor_account_id(accounts(0)).block_timestamp(DAY_MS * MS_TO_NANOS);
+        testing_env!(context.build(), near_sdk::test_vm_config(), near_sdk::RuntimeFeesConfig::test(), Default::default(), vec![result]);
+        contract.on_mint_callback(accounts(1), U128(1_000), None)
+    }
+
+    #[test]
+    fn failed_mint_is_pending_until_claimed() {
+        let (_, mut contract) = setup_contract();
+        assert_eq!(mint_callback_with_result(&mut contract, near_sdk::PromiseResult::Successful(vec![])), "Minting successful");
+        assert_eq!(contract.get_user_pending_rewards(accounts(1)), U128(0));
+        assert_eq!(mint_callback_with_result(&mut contract, near_sdk::PromiseResult::Failed), "Minting failed");
+        assert_eq!(mint_callback_with_result(&mut contract, near_sdk::PromiseResult::Failed), "Minting failed");
+        assert_eq!(contract.get_user_pending_rewards(accounts(1)), U128(2_000));
+
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_pending_rewards();
+        assert_eq!(contract.get_user_pending_rewards(accounts(1)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "No pending rewards for the user")]
+    fn claim_without_pending_rewards() {
+        let (_, mut contract) = setup_contract();
+        contract.claim_pending_rewards();
+    }
+
+    #[test]
+    fn accumulated_rewards_are_claimed_at_once() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_reward_claim_mode(true, to_yocto_u8(15));
+        let win = GameResult { won: true, collected_items: 0 };
+        for _ in 0..2 {
+            contract.get_seed_id(None);
+            contract.end_game(accounts(0), None, win.clone(), None);
+        }
+        assert_eq!(contract.get_user_claimable_rewards(accounts(0)), to_yocto_u8(20));
+        assert_eq!(contract.get_user_game_history(accounts(0), 0, 10).len(), 2);
+
+        contract.claim_rewards();
+        assert_eq!(contract.get_user_claimable_rewards(accounts(0)), U128(0));
+    }
+
+    #[test]
+    fn accumulated_rewards_are_claimed_in_pages() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.build());
+        // One entry per referral, more than a single call can mint
+        for index in 0..7 {
+            let referral = AccountId::from_str(&format!("referral{}.near", index)).unwrap();
+            contract.credit_reward(&accounts(1), to_yocto_u8(1).0, Some(referral));
+        }
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_rewards();
+        assert_eq!(contract.get_user_claimable_rewards(accounts(1)), to_yocto_u8(2));
+        testing_env!(context.build());
+        contract.claim_rewards();
+        assert_eq!(contract.get_user_claimable_rewards(accounts(1)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "are below the minimum claim")]
+    fn claim_below_minimum() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_reward_claim_mode(true, to_yocto_u8(15));
+        contract.get_seed_id(None);
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        contract.claim_rewards();
+    }
+
+    #[test]
+    fn reward_decreases_with_time() {
+        let (_, contract) = setup_contract();
+        let game = Game::default();
+        let game_duration = contract.get_game_duration(&game);
+        assert_eq!(contract.compute_reward(&game, 0, 0), to_yocto_u8(10).0);
+        // 20% is guaranteed, the remaining 80% decreases with time
+        assert_eq!(contract.compute_reward(&game, game_duration / 2, 0), to_yocto_u8(6).0);
+        assert_eq!(contract.compute_reward(&game, game_duration, 0), 0);
+    }
+
+    #[test]
+    fn collected_items_add_to_reward() {
+        let (_, contract) = setup_contract();
+        let game = Game::default();
+        let game_duration = contract.get_game_duration(&game);
+        assert_eq!(contract.compute_reward(&game, game_duration / 2, 2), to_yocto_u8(8).0);
+        assert_eq!(contract.compute_reward(&game, game_duration / 2, 50), to_yocto_u8(10).0);
+    }
+
+    #[test]
+    fn hard_games_cost_more_and_last_less() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        let user = accounts(0);
+        contract.get_seed_id(Some(Difficulty::Hard));
+        assert_eq!(contract.get_user_remaining_games(&user), (3, 0));
+        assert_eq!(contract.get_user_ongoing_game(user.clone()).unwrap().difficulty, Difficulty::Hard);
+
+        testing_env!(context.block_timestamp((DAY_MS + 2 * MIN_MS) * MS_TO_NANOS).build());
+        assert!(contract.get_user_ongoing_game(user).is_none());
+    }
+
+    #[test]
+    fn reward_is_capped_by_difficulty() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(Some(Difficulty::Easy));
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 10 }, None);
+        assert_eq!(contract.get_user_game_history(accounts(0), 0, 1)[0].reward, to_yocto_u8(5).0.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum for Normal games is 30000 ms")]
+    fn too_fast_win_is_rejected() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_min_game_duration(Difficulty::Normal, 30);
+        contract.get_seed_id(None);
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+    }
+
+    #[test]
+    fn too_fast_win_is_flagged() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_min_game_duration(Difficulty::Normal, 30);
+        contract.set_plausibility_policy(PlausibilityPolicy { reject_implausible: false });
+        contract.get_seed_id(None);
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        assert_eq!(contract.get_user_flagged_results(accounts(0)), 1);
+
+        contract.get_seed_id(None);
+        testing_env!(context.block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        assert_eq!(contract.get_flagged_accounts(0, 10), vec![(accounts(0), 1)]);
+    }
+
+    #[test]
+    fn banned_account_is_not_rewarded() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.grant_role(Role::Moderator, accounts(2));
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ban_account(accounts(1), "Bot".to_string(), Some(60));
+        assert_eq!(contract.get_ban(accounts(1)).unwrap().reason, "Bot");
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+        let record = &contract.get_user_game_history(accounts(1), 0, 1)[0];
+        assert_eq!(record.outcome, GameOutcome::Lost);
+        assert_eq!(record.reward, "0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is banned")]
+    fn banned_account_cant_start_games() {
+        let (mut context, mut contract) = setup_contract();
+        contract.ban_account(accounts(1), "Bot".to_string(), None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+    }
+
+    #[test]
+    fn ban_expires() {
+        let (mut context, mut contract) = setup_contract();
+        contract.ban_account(accounts(1), "Bot".to_string(), Some(60));
+        testing_env!(context.block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        assert!(contract.get_ban(accounts(1)).is_none());
+        assert_eq!(contract.get_bans(0, 10).len(), 1);
+    }
+
+    #[test]
+    fn banned_purchase_is_refunded() {
+        let (mut context, mut contract) = setup_contract();
+        contract.ban_account(accounts(1), "Bot".to_string(), None);
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        let refund = contract.ft_on_transfer(accounts(1), to_yocto_u8(15), String::new());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount == to_yocto_u8(15)));
+        assert_eq!(contract.get_user_remaining_games(&accounts(1)).1, 0);
+    }
+
+    #[test]
+    fn game_starts_have_a_cooldown() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_start_limits(StartLimits { cooldown_seconds: 30, max_games_per_hour: 0 });
+        assert!(contract.can_start_game(accounts(0)).can_start);
+        contract.get_seed_id(None);
+        let can_start = contract.can_start_game(accounts(0));
+        assert!(!can_start.can_start);
+        assert_eq!(can_start.available_at, Some(DAY_MS + 30_000));
+
+        testing_env!(context.block_timestamp((DAY_MS + 30_000) * MS_TO_NANOS).build());
+        contract.get_seed_id(None);
+    }
+
+    #[test]
+    fn daily_challenge_top_players_claim_rewards() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        let seed_ids: Vec<SeedId> = (1..3).map(|index| {
+            testing_env!(context.predecessor_account_id(accounts(index)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+            contract.start_daily_challenge()
+        }).collect();
+        assert_eq!(seed_ids[0], seed_ids[1]);
+        for index in 1..3 {
+            testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + index as u64 * 1000) * MS_TO_NANOS).build());
+            contract.end_game(accounts(3 - index), None, GameResult { won: true, collected_items: 0 }, None);
+        }
+        let ranking = contract.get_daily_challenge_ranking(1, 0, 10);
+        assert_eq!(ranking[0].account_id, accounts(2).to_string());
+        assert_eq!(ranking[1].account_id, accounts(1).to_string());
+        // Only ranked, nothing is minted when the game ends
+        assert_eq!(contract.get_remaining_mint_budget(), RewardLimits::default().daily_mint_budget);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.claim_daily_challenge_reward(1);
+        assert_eq!(contract.get_daily_challenge(1).unwrap().claimed, 1);
+        // Claims count against the daily limits like any other reward
+        assert_eq!(contract.get_user_remaining_daily_reward(accounts(2)), to_yocto_u8(50));
+        assert_eq!(contract.get_remaining_mint_budget().0, RewardLimits::default().daily_mint_budget.0 - to_yocto_u8(50).0);
+    }
+
+    #[test]
+    fn late_daily_challenge_players_are_timed_from_the_day_start() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp((DAY_MS + 1000) * MS_TO_NANOS).build());
+        contract.start_daily_challenge();
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 4000) * MS_TO_NANOS).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp((DAY_MS + 6000) * MS_TO_NANOS).build());
+        contract.start_daily_challenge();
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 6100) * MS_TO_NANOS).build());
+        contract.end_game(accounts(2), None, GameResult { won: true, collected_items: 0 }, None);
+
+        let ranking = contract.get_daily_challenge_ranking(1, 0, 10);
+        assert_eq!(ranking[0].account_id, accounts(1).to_string());
+        assert_eq!(ranking[0].duration, 4000);
+        assert_eq!(ranking[1].account_id, accounts(2).to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Reward exceeds the daily reward limit of the user")]
+    fn daily_challenge_claims_respect_the_user_daily_limit() {
+        let (mut context, mut contract) = setup_contract();
+        contract.set_reward_limits(RewardLimits {
+            max_per_game: to_yocto_u8(25),
+            max_per_user_daily: to_yocto_u8(40),
+            daily_mint_budget: to_yocto_u8(200),
+        });
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+        contract.start_daily_challenge();
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 1000) * MS_TO_NANOS).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.claim_daily_challenge_reward(1);
+    }
+
+    #[test]
+    fn first_race_finisher_takes_the_pot() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        let refund = contract.ft_on_transfer(accounts(2), to_yocto_u8(12), "{\"action\":\"join_race\",\"race_id\":1}".to_string());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount == to_yocto_u8(2)));
+
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        let seed_ids: Vec<SeedId> = (1..3).map(|index| {
+            testing_env!(context.predecessor_account_id(accounts(index)).build());
+            contract.start_race_game(1)
+        }).collect();
+        assert_eq!(seed_ids[0], seed_ids[1]);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.end_game(accounts(2), None, GameResult { won: true, collected_items: 0 }, None);
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+        let race = contract.get_race(1).unwrap();
+        assert_eq!(race.winner, Some(accounts(2).to_string()));
+        assert!(race.closed);
+        // 5% of the pot of 20 cheddar
+        assert_eq!(contract.get_treasury(), to_yocto_u8(1));
+        contract.withdraw_treasury(accounts(0), to_yocto_u8(1));
+        assert_eq!(contract.get_treasury().0, 0);
+    }
+
+    #[test]
+    fn unjoined_race_is_refunded_after_timeout() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        assert_eq!(contract.get_open_races(0, 10).len(), 1);
+
+        testing_env!(context.block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.refund_race(1);
+        assert!(contract.get_race(1).unwrap().closed);
+        assert!(contract.get_open_races(0, 10).is_empty());
+    }
+
+    #[test]
+    fn race_seed_is_hidden_until_the_opponent_joins() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        assert_eq!(contract.get_race(1).unwrap().seed_id, None);
+
+        contract.ft_on_transfer(accounts(2), to_yocto_u8(10), "{\"action\":\"join_race\",\"race_id\":1}".to_string());
+        let seed_id = contract.get_race(1).unwrap().seed_id;
+        assert!(seed_id.is_some());
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        assert_eq!(Some(contract.start_race_game(1)), seed_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Race timed out")]
+    fn race_games_cant_start_after_timeout() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        contract.ft_on_transfer(accounts(2), to_yocto_u8(10), "{\"action\":\"join_race\",\"race_id\":1}".to_string());
+
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.start_race_game(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Race can't be refunded before")]
+    fn race_is_not_refunded_before_timeout() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        contract.refund_race(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Daily challenge already played today")]
+    fn daily_challenge_is_played_once_per_day() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.start_daily_challenge();
+        contract.forfeit_game(None);
+        contract.start_daily_challenge();
+    }
+
+    #[test]
+    fn concurrent_games_are_settled_by_id() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_max_concurrent_games(2);
+        let seed_ids: Vec<SeedId> = (0..2).map(|_| contract.get_seed_id(None)).collect();
+        let games = contract.get_user_ongoing_games(accounts(0));
+        assert_eq!(games.iter().map(|game| game.seed_id).collect::<Vec<_>>(), seed_ids);
+
+        contract.end_game(accounts(0), Some(games[0].game_id), GameResult { won: false, collected_items: 0 }, None);
+        let games_left = contract.get_user_ongoing_games(accounts(0));
+        assert_eq!(games_left.len(), 1);
+        assert_eq!(games_left[0].game_id, games[1].game_id);
+    }
+
+    #[test]
+    fn extension_bought_with_cheddar_moves_the_deadline() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        let deadline = contract.get_user_ongoing_game(accounts(0)).unwrap().deadline;
+        assert_eq!(deadline, DAY_MS + 3 * MIN_MS);
+
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        let refund = contract.ft_on_transfer(accounts(0), to_yocto_u8(6), "{\"action\":\"extend\",\"seconds\":60}".to_string());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount == to_yocto_u8(1)));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 3 * MIN_MS) * MS_TO_NANOS).build());
+        let game = contract.get_user_ongoing_game(accounts(0)).unwrap();
+        assert_eq!(game.deadline, deadline + MIN_MS);
+        assert_eq!(game.extensions, 1);
+    }
+
+    #[test]
+    fn extensions_do_not_raise_the_reward() {
+        let (_, contract) = setup_contract();
+        let game = Game::default();
+        let extended_game = Game { extensions: 1, extended_ms: MIN_MS, ..Default::default() };
+        let base_duration = contract.get_game_duration(&game);
+        assert_eq!(contract.compute_reward(&extended_game, base_duration / 2, 0), contract.compute_reward(&game, base_duration / 2, 0));
+        // Winning in the bought time only gets the guaranteed 20%
+        assert_eq!(contract.compute_reward(&extended_game, base_duration + MIN_MS / 2, 0), to_yocto_u8(2).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game can't be extended more than 2 times")]
+    fn extensions_are_limited() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(0), to_yocto_u8(100), String::new());
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        for _ in 0..3 {
+            contract.extend_game_with_paid_game(None);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is banned")]
+    fn banned_accounts_cant_extend_games() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(100), String::new());
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.ban_account(accounts(1), "Bot".to_string(), None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.extend_game_with_paid_game(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Purchases are paused")]
+    fn paused_purchases_stop_paid_game_extensions() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(0), to_yocto_u8(100), String::new());
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_pause_flags(PauseFlags { purchases: true, game_starts: false, settlement: false });
+        contract.extend_game_with_paid_game(None);
+    }
+
+    #[test]
+    fn wins_unlock_achievements() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        testing_env!(context.block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        let unlocked: Vec<String> = contract.get_user_achievements(accounts(0)).into_iter().map(|achievement| achievement.achievement_id).collect();
+        assert_eq!(unlocked, vec!["first_win".to_string()]);
+
+        contract.get_seed_id(None);
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        assert_eq!(contract.get_user_achievements(accounts(0)).len(), 2);
+    }
+
+    #[test]
+    fn badges_are_claimed_and_paid_by_the_player() {
+        let (mut context, mut contract) = setup_contract();
+        let mint_deposit = 10_000_000_000_000_000_000_000;
+        contract.set_badge_config(Some(achievements::BadgeConfig {
+            badge_contract: AccountId::from_str("badges.near").unwrap(),
+            mint_deposit: U128(mint_deposit),
+        }));
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+        contract.end_game(accounts(1), None, GameResult { won: true, collected_items: 0 }, None);
+        // Nothing is minted until the player claims, and pays for, the badge
+        assert!(!contract.get_user_achievements(accounts(1))[0].badge_minted);
+
+        context.attached_deposit(NearToken::from_yoctonear(mint_deposit + 1_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_badge("first_win".to_string());
+        assert!(contract.get_user_achievements(accounts(1))[0].badge_minted);
+        assert_eq!(transfers_to(&accounts(1)), vec![1_000]);
+        // Argument names of the reference NEP-171 nft_mint
+        let mint_args: Vec<String> = near_sdk::test_utils::get_created_receipts().into_iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == "badges.near")
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                near_sdk::mock::MockAction::FunctionCallWeight { method_name, args, .. } if method_name == b"nft_mint" => Some(String::from_utf8(args).unwrap()),
+                _ => None,
+            })
+            .collect();
+        assert!(mint_args[0].contains(&format!("\"token_owner_id\":\"{}\"", accounts(1))));
+
+        let mut callback_context = VMContextBuilder::new();
+        callback_context.current_account_id(accounts(0)).predecessor_account_id(accounts(0)).block_timestamp(DAY_MS * MS_TO_NANOS);
+        testing_env!(callback_context.build(), near_sdk::test_vm_config(), near_sdk::RuntimeFeesConfig::test(), Default::default(), vec![near_sdk::PromiseResult::Failed]);
+        assert!(!contract.on_badge_mint(accounts(1), "first_win".to_string(), U128(mint_deposit)));
+        assert!(!contract.get_user_achievements(accounts(1))[0].badge_minted);
+        assert_eq!(transfers_to(&accounts(1)), vec![mint_deposit]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit must be at least 10000000000000000000000 yoctoNEAR")]
+    fn badge_claim_needs_the_mint_deposit() {
+        let (mut context, mut contract) = setup_contract();
+        contract.set_badge_config(Some(achievements::BadgeConfig {
+            badge_contract: AccountId::from_str("badges.near").unwrap(),
+            mint_deposit: U128(10_000_000_000_000_000_000_000),
+        }));
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.claim_badge("first_win".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is banned")]
+    fn banned_accounts_cant_claim_badges() {
+        let (mut context, mut contract) = setup_contract();
+        contract.set_badge_config(Some(achievements::BadgeConfig {
+            badge_contract: AccountId::from_str("badges.near").unwrap(),
+            mint_deposit: U128(10_000_000_000_000_000_000_000),
+        }));
+        contract.ban_account(accounts(1), "Bot".to_string(), None);
+        context.attached_deposit(NearToken::from_yoctonear(10_000_000_000_000_000_000_000));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.claim_badge("first_win".to_string());
+    }
+
+    #[test]
+    fn win_streak_achievement() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        contract.set_achievement(Achievement {
+            achievement_id: "three_day_streak".to_string(),
+            title: "3-day streak".to_string(),
+            condition: achievements::AchievementCondition::WinStreak { days: 3 },
+        });
+        for day in 1..4 {
+            testing_env!(context.block_timestamp((day * DAY_MS + MIN_MS) * MS_TO_NANOS).build());
+            assert!(!contract.get_user_achievements(accounts(0)).iter().any(|achievement| achievement.achievement_id == "three_day_streak"));
+            contract.get_seed_id(None);
+            contract.end_game(accounts(0), None, GameResult { won: false, collected_items: 0 }, None);
+            contract.get_seed_id(None);
+            testing_env!(context.block_timestamp((day * DAY_MS + 2 * MIN_MS) * MS_TO_NANOS).build());
+            contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+        }
+        assert!(contract.get_user_achievements(accounts(0)).iter().any(|achievement| achievement.achievement_id == "three_day_streak"));
+    }
+
+    #[test]
+    fn game_operator_settles_games() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        contract.grant_role(Role::GameOperator, accounts(3));
+        assert_eq!(contract.get_role_members(Role::GameOperator), vec![accounts(3)]);
+        assert_eq!(contract.get_account_roles(accounts(3)), vec![Role::GameOperator]);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.get_seed_id(None);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.end_game(accounts(1), None, GameResult { won: false, collected_items: 0 }, None);
+        assert!(contract.get_user_ongoing_game(accounts(1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this function.")]
+    fn game_operator_cant_change_minter() {
+        let (mut context, mut contract) = setup_contract();
+        contract.grant_role(Role::GameOperator, accounts(3));
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.set_maze_minter_contract(accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or an account with the Admin role can call this method")]
+    fn revoked_admin_loses_access() {
+        let (mut context, mut contract) = setup_contract();
+        contract.grant_role(Role::Admin, accounts(1));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.grant_role(Role::PricingManager, accounts(2));
+        contract.insert_game_cost(1, to_yocto_u8(20));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.revoke_role(Role::Admin, accounts(1));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_max_game_duration(60);
+    }
+
+    #[test]
+    fn ownership_is_transferred_in_two_steps() {
+        let (mut context, mut contract) = setup_contract();
+        contract.propose_owner(accounts(1));
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(1)));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_ownership();
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert!(contract.get_pending_owner().is_none());
+        assert!(near_sdk::test_utils::get_logs()[0].starts_with("EVENT_JSON:"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept the ownership")]
+    fn cancelled_ownership_proposal_cant_be_accepted() {
+        let (mut context, mut contract) = setup_contract();
+        contract.propose_owner(accounts(1));
+        contract.cancel_owner_proposal();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_ownership();
+    }
+
+    #[test]
+    fn paused_purchases_are_refunded() {
+        let (mut context, mut contract) = setup_contract();
+        contract.grant_role(Role::Pauser, accounts(1));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.pause_all();
+        assert!(contract.get_contract_state().pause_flags.purchases);
+
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        let refund = contract.ft_on_transfer(accounts(2), to_yocto_u8(15), String::new());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount == to_yocto_u8(15)));
+        assert_eq!(contract.get_user_remaining_paid_games(&accounts(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement and minting are paused")]
+    fn paused_settlement_blocks_end_game() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        contract.set_pause_flags(PauseFlags { purchases: false, game_starts: false, settlement: true });
+        contract.end_game(accounts(0), None, GameResult { won: true, collected_items: 0 }, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement and minting are paused")]
+    fn paused_settlement_blocks_forfeits() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.get_seed_id(None);
+        contract.set_pause_flags(PauseFlags { purchases: false, game_starts: false, settlement: true });
+        contract.forfeit_game(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement and minting are paused")]
+    fn paused_settlement_blocks_tournament_payouts() {
+        let (mut context, mut contract) = setup_contract();
+        let tournament_id = setup_tournament(&mut context, &mut contract);
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp((DAY_MS + 10 * MIN_MS) * MS_TO_NANOS).build());
+        contract.set_pause_flags(PauseFlags { purchases: false, game_starts: false, settlement: true });
+        contract.finalize_tournament(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Settlement and minting are paused")]
+    fn paused_settlement_blocks_race_refunds() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(1), to_yocto_u8(10), "{\"action\":\"create_race\"}".to_string());
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.set_pause_flags(PauseFlags { purchases: false, game_starts: false, settlement: true });
+        contract.refund_race(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game starts are paused")]
+    fn paused_game_starts() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_pause_flags(PauseFlags { purchases: false, game_starts: true, settlement: false });
+        assert_eq!(contract.can_start_game(accounts(0)).reason, Some("Game starts are paused".to_string()));
+        contract.get_seed_id(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal can't be executed before")]
+    fn proposal_waits_for_the_timelock() {
+        let (_, mut contract) = setup_contract();
+        let proposal_id = contract.set_maze_minter_contract(accounts(3));
+        let pending = contract.get_pending_proposals(0, 10);
+        assert_eq!(pending[0].executable_at, 2 * DAY_MS);
+        contract.execute_proposal(proposal_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal not found")]
+    fn cancelled_proposal_cant_be_executed() {
+        let (mut context, mut contract) = setup_contract();
+        let proposal_id = contract.insert_game_cost(5, to_yocto_u8(10));
+        contract.cancel_proposal(proposal_id);
+        assert!(contract.get_pending_proposals(0, 10).is_empty());
+        execute_after_delay(&mut context, &mut contract, proposal_id);
+    }
+
+    #[test]
+    fn update_config_applies_patch() {
+        let (_, mut contract) = setup_contract();
+        let proposal_id = contract.update_config(config::ConfigPatch {
+            min_deposit: Some(U128(2_000_000_000_000_000_000_000)),
+            max_game_duration_seconds: Some(240),
+            max_concurrent_games: Some(3),
+            bond_policy: Some(BondPolicy { keep_on_forfeit: false, keep_on_expiry: true }),
+            reward_limits: Some(reward_limits::RewardLimits {
+                max_per_game: to_yocto_u8(20),
+                max_per_user_daily: to_yocto_u8(60),
+                daily_mint_budget: to_yocto_u8(200),
+            }),
+            reward_schedule: Some(reward_schedule::RewardSchedule {
+                min_time_share: 3_000,
+                item_reward: to_yocto_u8(2),
+                max_items: 5,
+            }),
+            accumulate_rewards: Some(true),
+            min_claim_amount: Some(to_yocto_u8(10)),
+            start_limits: Some(start_limits::StartLimits { cooldown_seconds: 30, max_games_per_hour: 12 }),
+            race_config: Some(race::RaceConfig { house_fee: 300, timeout: 2 * MIN_MS }),
+            plausibility_policy: Some(anti_cheat::PlausibilityPolicy { reject_implausible: false }),
+            min_game_durations: Some(vec![(Difficulty::Normal, 20), (Difficulty::Hard, 10)]),
+            daily_challenge_rewards: Some(vec![to_yocto_u8(30), to_yocto_u8(10)]),
+            badge_config: Some(Some(achievements::BadgeConfig {
+                badge_contract: accounts(5),
+                mint_deposit: U128(10_000_000_000_000_000_000_000),
+            })),
+            ..Default::default()
+        });
+        assert!(proposal_id.is_none());
+
+        let state = contract.get_contract_state();
+        assert_eq!(state.min_deposit, "2000000000000000000000");
+        assert_eq!(state.max_game_duration, 4 * MIN_MS);
+        assert_eq!(state.max_concurrent_games, 3);
+        assert!(!state.bond_policy.keep_on_forfeit);
+        assert!(state.bond_policy.keep_on_expiry);
+        assert_eq!(state.reward_limits.max_per_game, to_yocto_u8(20));
+        assert_eq!(state.reward_limits.max_per_user_daily, to_yocto_u8(60));
+        assert_eq!(state.reward_limits.daily_mint_budget, to_yocto_u8(200));
+        assert_eq!(state.reward_schedule.min_time_share, 3_000);
+        assert_eq!(state.reward_schedule.item_reward, to_yocto_u8(2));
+        assert_eq!(state.reward_schedule.max_items, 5);
+        assert!(state.accumulate_rewards);
+        assert_eq!(state.min_claim_amount, to_yocto_u8(10).0.to_string());
+        assert_eq!(state.start_limits.cooldown_seconds, 30);
+        assert_eq!(state.start_limits.max_games_per_hour, 12);
+        assert!(!state.plausibility_policy.reject_implausible);
+        let race_config = contract.get_race_config();
+        assert_eq!(race_config.house_fee, 300);
+        assert_eq!(race_config.timeout, 2 * MIN_MS);
+        let mut min_game_durations = contract.get_min_game_durations();
+        min_game_durations.sort_by_key(|(_, min_game_duration)| *min_game_duration);
+        assert_eq!(min_game_durations, [(Difficulty::Hard, 10_000), (Difficulty::Normal, 20_000)]);
+        assert_eq!(contract.get_daily_challenge_rewards(), [to_yocto_u8(30), to_yocto_u8(10)]);
+        assert_eq!(contract.get_badge_config().unwrap().badge_contract, accounts(5));
+
+        // 0 removes a minimum and null turns badges off
+        let patch: config::ConfigPatch = near_sdk::serde_json::from_str(r#"{"min_game_durations":[["hard",0]],"badge_config":null}"#).unwrap();
+        contract.update_config(patch);
+        assert_eq!(contract.get_min_game_durations(), [(Difficulty::Normal, 20_000)]);
+        assert!(contract.get_badge_config().is_none());
+        let patch: config::ConfigPatch = near_sdk::serde_json::from_str("{}").unwrap();
+        assert!(patch.badge_config.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Max reward per game can't be more than the daily limit of a user")]
+    fn update_config_rejects_reward_per_game_over_daily_limit() {
+        let (_, mut contract) = setup_contract();
+        contract.update_config(config::ConfigPatch {
+            reward_limits: Some(reward_limits::RewardLimits {
+                max_per_game: to_yocto_u8(20),
+                max_per_user_daily: to_yocto_u8(10),
+                daily_mint_budget: to_yocto_u8(200),
+            }),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Race timeout must be greater than 0")]
+    fn update_config_rejects_zero_race_timeout() {
+        let (_, mut contract) = setup_contract();
+        contract.update_config(config::ConfigPatch {
+            race_config: Some(race::RaceConfig { house_fee: 300, timeout: 0 }),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Game duration of Normal must be more than its minimum duration of 240000 ms")]
+    fn update_config_checks_min_duration_against_patched_duration() {
+        let (_, mut contract) = setup_contract();
+        contract.update_config(config::ConfigPatch {
+            max_game_duration_seconds: Some(200),
+            min_game_durations: Some(vec![(Difficulty::Normal, 240)]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn update_config_timelocks_pricing_patch_as_a_whole() {
+        let (mut context, mut contract) = setup_contract();
+        let proposal_id = contract.update_config(config::ConfigPatch {
+            min_deposit: Some(U128(2_000_000_000_000_000_000_000)),
+            difficulty_levels: Some(vec![config::DifficultyLevel {
+                difficulty: Difficulty::Hard,
+                cost_multiplier: 3,
+                game_duration_seconds: 90,
+                max_reward: to_yocto_u8(30),
+            }]),
+            game_costs: Some(vec![(1, to_yocto_u8(12)), (5, to_yocto_u8(11))]),
+            extension_config: Some(extension::ExtensionConfig {
+                max_extensions: 1,
+                max_seconds: 60,
+                cost_per_minute: to_yocto_u8(8),
+                seconds_per_paid_game: 30,
+            }),
+            ..Default::default()
+        }).expect("Pricing patch must be proposed");
+
+        let state = contract.get_contract_state();
+        assert_eq!(state.min_deposit, "1000000000000000000000");
+        assert_eq!(state.extension_config.max_extensions, 2);
+        let game_costs = [["1".to_string(), to_yocto_u8(15).0.to_string()], ["10".to_string(), to_yocto_u8(14).0.to_string()]];
+        assert_eq!(contract.get_games_costs(), game_costs);
+
+        execute_after_delay(&mut context, &mut contract, proposal_id);
+        let state = contract.get_contract_state();
+        assert_eq!(state.min_deposit, "2000000000000000000000");
+        let game_costs = [["1".to_string(), to_yocto_u8(12).0.to_string()], ["5".to_string(), to_yocto_u8(11).0.to_string()]];
+        assert_eq!(contract.get_games_costs(), game_costs);
+        let hard = contract.get_difficulty_levels().into_iter().find(|level| level.difficulty == Difficulty::Hard).unwrap();
+        assert_eq!(hard.cost_multiplier, 3);
+        assert_eq!(hard.max_game_duration, 90 * 1000);
+        assert_eq!(hard.max_reward, to_yocto_u8(30).0.to_string());
+        let extension_config = contract.get_extension_config();
+        assert_eq!(extension_config.max_extensions, 1);
+        assert_eq!(extension_config.max_seconds, 60);
+        assert_eq!(extension_config.cost_per_minute, to_yocto_u8(8));
+        assert_eq!(extension_config.seconds_per_paid_game, 30);
+    }
+
+    #[test]
+    fn patched_game_costs_are_sorted_for_purchases() {
+        let (mut context, mut contract) = setup_contract();
+        let proposal_id = contract.update_config(config::ConfigPatch {
+            game_costs: Some(vec![(5, to_yocto_u8(11)), (1, to_yocto_u8(12))]),
+            ..Default::default()
+        }).unwrap();
+        execute_after_delay(&mut context, &mut contract, proposal_id);
+        let game_costs = [["1".to_string(), to_yocto_u8(12).0.to_string()], ["5".to_string(), to_yocto_u8(11).0.to_string()]];
+        assert_eq!(contract.get_games_costs(), game_costs);
+
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        let refund = contract.ft_on_transfer(accounts(1), to_yocto_u8(24), String::new());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount.0 == 0));
+        assert_eq!(contract.get_user_remaining_paid_games(&accounts(1)), 2);
+        let refund = contract.ft_on_transfer(accounts(2), to_yocto_u8(60), String::new());
+        assert!(matches!(refund, PromiseOrValue::Value(amount) if amount == to_yocto_u8(5)));
+        assert_eq!(contract.get_user_remaining_paid_games(&accounts(2)), 5);
+    }
+
+    #[test]
+    fn inserted_game_costs_are_sorted() {
+        let (mut context, mut contract) = setup_contract();
+        let proposal_id = contract.insert_game_cost(5, to_yocto_u8(14));
+        execute_after_delay(&mut context, &mut contract, proposal_id);
+        let keys: Vec<String> = contract.get_games_costs().into_iter().map(|[key, _]| key).collect();
+        assert_eq!(keys, ["1", "5", "10"]);
+    }
+
+    #[test]
+    fn difficulty_and_extension_pricing_is_timelocked() {
+        let (mut context, mut contract) = setup_contract();
+        let level_proposal = contract.set_difficulty_level(Difficulty::Hard, 3, 90, to_yocto_u8(30));
+        let extension_proposal = contract.set_extension_config(extension::ExtensionConfig {
+            max_extensions: 1,
+            max_seconds: 60,
+            cost_per_minute: to_yocto_u8(8),
+            seconds_per_paid_game: 30,
+        });
+        let hard = contract.get_difficulty_levels().into_iter().find(|level| level.difficulty == Difficulty::Hard).unwrap();
+        assert_eq!(hard.cost_multiplier, 2);
+        assert_eq!(contract.get_extension_config().cost_per_minute, to_yocto_u8(5));
+
+        execute_after_delay(&mut context, &mut contract, level_proposal);
+        execute_after_delay(&mut context, &mut contract, extension_proposal);
+        let hard = contract.get_difficulty_levels().into_iter().find(|level| level.difficulty == Difficulty::Hard).unwrap();
+        assert_eq!(hard.cost_multiplier, 3);
+        assert_eq!(hard.max_game_duration, 90_000);
+        assert_eq!(contract.get_extension_config().cost_per_minute, to_yocto_u8(8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Game costs must include a single game")]
+    fn update_config_rejects_game_costs_without_single_game() {
+        let (_, mut contract) = setup_contract();
+        contract.update_config(config::ConfigPatch {
+            min_deposit: Some(U128(2_000_000_000_000_000_000_000)),
+            game_costs: Some(vec![(5, to_yocto_u8(11))]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Min deposit must be greater than 0")]
+    fn update_config_rejects_zero_deposit() {
+        let (_, mut contract) = setup_contract();
+        contract.update_config(config::ConfigPatch {
+            min_deposit: Some(U128(0)),
+            ..Default::default()
+        });
+    }
+
+    fn enable_multisig(contract: &mut MazeGameBuyerContract) {
+        contract.set_multisig_config(MultisigConfig {
+            approvers: vec![accounts(1), accounts(2), accounts(3)],
+            threshold: 2,
+            request_lifetime: DAY_MS,
+        });
+    }
+
+    #[test]
+    fn multisig_request_executes_at_threshold() {
+        let (mut context, mut contract) = setup_contract();
+        enable_multisig(&mut contract);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let request_id = contract.create_multisig_request(multisig::MultisigAction::ProposeOwner { new_owner_id: accounts(4) });
+        assert!(contract.get_pending_owner().is_none());
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.confirm_multisig_request(request_id);
+        assert_eq!(contract.get_pending_owner(), Some(accounts(4)));
+        assert!(contract.get_multisig_requests(0, 10).is_empty());
+    }
+
+    #[test]
+    fn treasury_is_withdrawn_through_the_multisig() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context.predecessor_account_id(AccountId::from_str("token.cheddar.near").unwrap()).build());
+        contract.ft_on_transfer(accounts(4), to_yocto_u8(31), String::new());
+        // Two games at 15, the remaining cheddar is refunded
+        assert_eq!(contract.get_treasury(), to_yocto_u8(30));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        enable_multisig(&mut contract);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let request_id = contract.create_multisig_request(multisig::MultisigAction::Withdraw { receiver_id: accounts(5), amount: to_yocto_u8(20) });
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.confirm_multisig_request(request_id);
+        assert_eq!(contract.get_treasury(), to_yocto_u8(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "This action needs multisig approval")]
+    fn owner_alone_cant_withdraw_with_multisig() {
+        let (_, mut contract) = setup_contract();
+        enable_multisig(&mut contract);
+        contract.withdraw_treasury(accounts(0), U128(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "This action needs multisig approval")]
+    fn owner_alone_cant_change_minter_with_multisig() {
+        let (_, mut contract) = setup_contract();
+        enable_multisig(&mut contract);
+        contract.set_maze_minter_contract(accounts(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Multisig request expired")]
+    fn stale_multisig_request_cant_be_confirmed() {
+        let (mut context, mut contract) = setup_contract();
+        enable_multisig(&mut contract);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let request_id = contract.create_multisig_request(multisig::MultisigAction::MazeMinterContract { account_id: accounts(4) });
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(2 * DAY_MS * MS_TO_NANOS).build());
+        contract.confirm_multisig_request(request_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Limit of 2 ongoing games reached")]
+    fn starts_over_the_limit_are_rejected() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_max_concurrent_games(2);
+        contract.get_seed_id(None);
+        contract.get_seed_id(None);
+        assert!(!contract.can_start_game(accounts(0)).can_start);
+        contract.get_seed_id(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Limit of 2 games per hour reached")]
+    fn game_starts_are_limited_per_hour() {
+        let (mut context, mut contract) = setup_contract();
+        context.attached_deposit(NearToken::from_yoctonear(1_000_000_000_000_000_000_000));
+        testing_env!(context.build());
+        contract.set_start_limits(StartLimits { cooldown_seconds: 0, max_games_per_hour: 2 });
+        for _ in 0..3 {
+            contract.get_seed_id(None);
+        }
+    }
+
+    #[test]
+    fn migrate_keeps_the_deployed_state() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(accounts(0)).block_timestamp(DAY_MS * MS_TO_NANOS).build());
+        let today = get_today_day();
+        // Appended out of order, like insert_game_cost used to do
+        let mut game_costs = IterableMap::new(b"game_costs".to_vec());
+        game_costs.insert(10, to_yocto_u8(14).0);
+        game_costs.insert(1, to_yocto_u8(15).0);
+        game_costs.flush();
+        let mut user_remaining_free_games = UnorderedMap::new(b"free_games".to_vec());
+        user_remaining_free_games.insert(&accounts(1), &FreeGameInfo { day: today, amount: 2 });
+        let mut user_remaining_paid_games = UnorderedMap::new(b"paid_games".to_vec());
+        user_remaining_paid_games.insert(&accounts(2), &7);
+        let mut ongoing_games = UnorderedMap::new(b"ongoing_games".to_vec());
+        ongoing_games.insert(&accounts(1), &OldGame { seed_id: 3, start_time: DAY_MS, is_ending_game: false });
+        env::state_write(&OldMazeGameBuyerContract {
+            owner_id: accounts(0),
+            cheddar_contract: AccountId::from_str("token.cheddar.near").unwrap(),
+            game_costs,
+            user_remaining_free_games,
+            user_remaining_paid_games,
+            seed_id: 3,
+            min_deposit: 2_000_000_000_000_000_000_000,
+            ongoing_games,
+            maze_minter_contract: AccountId::from_str("minter.near").unwrap(),
+            max_game_duration: 4 * MIN_MS,
+        });
+
+        let contract = MazeGameBuyerContract::migrate();
+
+        let state = contract.get_contract_state();
+        assert_eq!(state.owner_id, accounts(0).to_string());
+        assert_eq!(state.cheddar_contract, "token.cheddar.near");
+        assert_eq!(state.maze_minter_contract, "minter.near");
+        assert_eq!(state.seed_id, 3);
+        assert_eq!(state.min_deposit, "2000000000000000000000");
+        assert_eq!(state.max_game_duration, 4 * MIN_MS);
+        assert_eq!(contract.get_games_costs(), [["1".to_string(), to_yocto_u8(15).0.to_string()], ["10".to_string(), to_yocto_u8(14).0.to_string()]]);
+        assert_eq!(contract.get_user_remaining_free_games(&accounts(1)), 2);
+        assert_eq!(contract.get_user_remaining_free_games(&accounts(2)), 5);
+        assert_eq!(contract.get_user_remaining_paid_games(&accounts(2)), 7);
+        assert_eq!(contract.get_user_remaining_paid_games(&accounts(1)), 0);
+        let game = contract.get_ongoing_game(&accounts(1), 1).unwrap();
+        assert_eq!(game.seed_id, 3);
+        assert_eq!(game.start_time, DAY_MS);
+        assert_eq!(game.difficulty, Difficulty::Normal);
+        assert_eq!(game.bond, 2_000_000_000_000_000_000_000);
+        let old_game_key = [b"ongoing_games".as_slice(), b"v", &0u64.to_le_bytes()].concat();
+        assert!(!env::storage_has_key(&old_game_key));
+    }
+
 }
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::structs::multisig_request_json::MultisigRequestJson;
+use crate::timelock::ConfigChange;
+use crate::utils::get_now_ms;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+pub type RequestId = u64;
+
+/// Once set, the sensitive actions need `threshold` of the approvers instead of the owner alone
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigConfig {
+    #[schemars(with = "Vec<String>")]
+    pub approvers: Vec<AccountId>,
+    pub threshold: u8,
+    // Requests that don't get enough confirmations in this time can't be executed anymore
+    pub request_lifetime: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+pub enum MultisigAction {
+    // Contract address changes still go through the timelock once approved
+    CheddarContract {
+        #[schemars(with = "String")]
+        account_id: AccountId,
+    },
+    MazeMinterContract {
+        #[schemars(with = "String")]
+        account_id: AccountId,
+    },
+    ProposeOwner {
+        #[schemars(with = "String")]
+        new_owner_id: AccountId,
+    },
+    // Without a config the multisig is disabled and the owner acts alone again
+    SetMultisig { config: Option<MultisigConfig> },
+    // Sends cheddar out of the treasury
+    Withdraw {
+        #[schemars(with = "String")]
+        receiver_id: AccountId,
+        #[schemars(with = "String")]
+        amount: U128,
+    },
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MultisigRequest {
+    pub action: MultisigAction,
+    pub created_by: AccountId,
+    pub expires_at: u64,
+    pub confirmations: Vec<AccountId>,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_multisig_config(&self) -> Option<MultisigConfig> {
+        self.multisig_config.clone()
+    }
+
+    /// Turns the multisig on. From then on it can only be changed or disabled through a SetMultisig request
+    pub fn set_multisig_config(&mut self, config: MultisigConfig) {
+        self.assert_owner_acts_alone();
+        self.apply_multisig_config(Some(config));
+    }
+
+    /// Creating a request counts as the first confirmation
+    pub fn create_multisig_request(&mut self, action: MultisigAction) -> RequestId {
+        let config = self.assert_approver();
+        let approver = env::predecessor_account_id();
+        self.last_request_id += 1;
+        log!("Multisig request {} created by {}: {:?}", self.last_request_id, approver, action);
+        self.multisig_requests.insert(&self.last_request_id, &MultisigRequest {
+            action,
+            created_by: approver.clone(),
+            expires_at: get_now_ms() + config.request_lifetime,
+            confirmations: vec![],
+        });
+        self.confirm_multisig_request(self.last_request_id);
+        self.last_request_id
+    }
+
+    /// Executes the request once it reaches the threshold
+    pub fn confirm_multisig_request(&mut self, request_id: RequestId) {
+        let config = self.assert_approver();
+        let approver = env::predecessor_account_id();
+        let mut request = self.multisig_requests.get(&request_id).expect("Multisig request not found");
+        require!(get_now_ms() < request.expires_at, "Multisig request expired");
+        require!(!request.confirmations.contains(&approver), "Multisig request already confirmed by the approver");
+        request.confirmations.push(approver);
+
+        if request.confirmations.len() < config.threshold as usize {
+            self.multisig_requests.insert(&request_id, &request);
+            return;
+        }
+        self.multisig_requests.remove(&request_id);
+        log!("Multisig request {} approved", request_id);
+        self.execute_multisig_action(request.action);
+    }
+
+    /// Removes a request that expired. Anyone can call it
+    pub fn remove_expired_multisig_request(&mut self, request_id: RequestId) {
+        let request = self.multisig_requests.get(&request_id).expect("Multisig request not found");
+        require!(get_now_ms() >= request.expires_at, "Multisig request has not expired yet");
+        self.multisig_requests.remove(&request_id);
+    }
+
+    pub fn get_multisig_requests(&self, from: u64, limit: u64) -> Vec<MultisigRequestJson> {
+        self.multisig_requests.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(request_id, request)| MultisigRequestJson::from_request(request_id, &request))
+            .collect()
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    /// For the sensitive actions: the owner alone while there is no multisig
+    pub(crate) fn assert_owner_acts_alone(&self) {
+        self.assert_only_owner();
+        require!(self.multisig_config.is_none(), "This action needs multisig approval. Use create_multisig_request");
+    }
+
+    fn assert_approver(&self) -> MultisigConfig {
+        let config = self.multisig_config.clone().expect("Multisig is not enabled");
+        require!(config.approvers.contains(&env::predecessor_account_id()), "Only multisig approvers can call this method");
+        config
+    }
+
+    fn apply_multisig_config(&mut self, config: Option<MultisigConfig>) {
+        if let Some(config) = &config {
+            let unique_approvers: HashSet<&AccountId> = config.approvers.iter().collect();
+            require!(unique_approvers.len() == config.approvers.len(), "Approvers must be unique");
+            require!(config.threshold > 0 && config.threshold as usize <= config.approvers.len(), "Threshold must be between 1 and the amount of approvers");
+            require!(config.request_lifetime > 0, "Request lifetime must be greater than 0");
+        }
+        // Confirmations were given under the previous approver set
+        self.multisig_requests.clear();
+        self.multisig_config = config;
+    }
+
+    fn execute_multisig_action(&mut self, action: MultisigAction) {
+        match action {
+            MultisigAction::CheddarContract { account_id } => {
+                self.propose_config_change(ConfigChange::CheddarContract { account_id });
+            }
+            MultisigAction::MazeMinterContract { account_id } => {
+                self.propose_config_change(ConfigChange::MazeMinterContract { account_id });
+            }
+            MultisigAction::ProposeOwner { new_owner_id } => self.internal_propose_owner(new_owner_id),
+            MultisigAction::SetMultisig { config } => self.apply_multisig_config(config),
+            MultisigAction::Withdraw { receiver_id, amount } => {
+                self.internal_withdraw_treasury(receiver_id, amount.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, require, AccountId};
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+// NEP-297 events, so indexers can follow ownership changes
+fn emit_ownership_event(event: &str, old_owner_id: &AccountId, new_owner_id: &AccountId) {
+    let event = json!({
+        "standard": "maze-game-buyer",
+        "version": "1.0.0",
+        "event": event,
+        "data": [{ "old_owner_id": old_owner_id, "new_owner_id": new_owner_id }],
+    });
+    env::log_str(&format!("EVENT_JSON:{}", event));
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    /// First step of the transfer. The new owner takes over once it calls accept_ownership.
+    /// With the multisig enabled this goes through a ProposeOwner request instead
+    pub fn propose_owner(&mut self, new_owner_id: AccountId) {
+        self.assert_owner_acts_alone();
+        self.internal_propose_owner(new_owner_id);
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.assert_only_owner();
+        let pending_owner_id = self.pending_owner_id.take().expect("No ownership transfer proposed");
+        emit_ownership_event("ownership_proposal_cancelled", &self.owner_id, &pending_owner_id);
+    }
+
+    pub fn accept_ownership(&mut self) {
+        let new_owner_id = env::predecessor_account_id();
+        require!(self.pending_owner_id.as_ref() == Some(&new_owner_id), "Only the proposed owner can accept the ownership");
+        emit_ownership_event("ownership_transferred", &self.owner_id, &new_owner_id);
+        self.owner_id = new_owner_id;
+        self.pending_owner_id = None;
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn internal_propose_owner(&mut self, new_owner_id: AccountId) {
+        require!(new_owner_id != self.owner_id, "Account is already the owner");
+        emit_ownership_event("ownership_proposed", &self.owner_id, &new_owner_id);
+        self.pending_owner_id = Some(new_owner_id);
+    }
+}
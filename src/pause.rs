@@ -0,0 +1,57 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{log, near_bindgen, require};
+use schemars::JsonSchema;
+use crate::roles::Role;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// Circuit breakers for when the minter or the cheddar contract misbehaves.
+/// Paused purchases are refunded, and extensions paid with a paid game are rejected. Paused settlement stops
+/// everything that pays out: settling games, forfeits and expiries included, bond refunds, tournament and race
+/// payouts and claims. Starting a game that has to settle older games of the player fails too
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseFlags {
+    pub purchases: bool,
+    pub game_starts: bool,
+    pub settlement: bool,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_pause_flags(&self) -> PauseFlags {
+        self.pause_flags.clone()
+    }
+
+    pub fn set_pause_flags(&mut self, pause_flags: PauseFlags) {
+        self.assert_role(Role::Pauser);
+        log!("Pause flags set to {:?}", pause_flags);
+        self.pause_flags = pause_flags;
+    }
+
+    /// Pauses everything at once
+    pub fn pause_all(&mut self) {
+        self.set_pause_flags(PauseFlags {
+            purchases: true,
+            game_starts: true,
+            settlement: true,
+        });
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // For what is bought without a transfer, which ft_on_transfer can't refund
+    pub(crate) fn assert_purchases_not_paused(&self) {
+        require!(!self.pause_flags.purchases, "Purchases are paused");
+    }
+
+    pub(crate) fn assert_game_starts_not_paused(&self) {
+        require!(!self.pause_flags.game_starts, "Game starts are paused");
+    }
+
+    pub(crate) fn assert_settlement_not_paused(&self) {
+        require!(!self.pause_flags.settlement, "Settlement and minting are paused");
+    }
+}
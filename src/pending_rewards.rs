@@ -0,0 +1,55 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, AccountId, Promise};
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+// Each retry is a mint plus its callback, so only a few fit in one call
+pub const MAX_CLAIMS_PER_CALL: usize = 5;
+
+/// Reward whose mint failed. Kept until the player claims it again
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct PendingReward {
+    pub amount: Balance,
+    pub referral: Option<AccountId>,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_user_pending_rewards(&self, account_id: AccountId) -> U128 {
+        let pending_rewards = self.pending_rewards.get(&account_id).unwrap_or_default();
+        U128(pending_rewards.iter().map(|pending_reward| pending_reward.amount).sum())
+    }
+
+    /// Retries the mints that failed for the caller. Mints that fail again go back to the ledger
+    pub fn claim_pending_rewards(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        self.assert_settlement_not_paused();
+        let mut pending_rewards = self.pending_rewards.get(&account_id).unwrap_or_default();
+        assert!(!pending_rewards.is_empty(), "No pending rewards for the user");
+
+        let claimed: Vec<PendingReward> = pending_rewards.drain(..pending_rewards.len().min(MAX_CLAIMS_PER_CALL)).collect();
+        if pending_rewards.is_empty() {
+            self.pending_rewards.remove(&account_id);
+        } else {
+            self.pending_rewards.insert(&account_id, &pending_rewards);
+        }
+
+        log!("Claiming {} pending rewards for {}", claimed.len(), account_id);
+        claimed.into_iter()
+            .map(|pending_reward| self.mint_reward(account_id.clone(), pending_reward.amount, pending_reward.referral))
+            .reduce(|promise, mint| promise.and(mint))
+            .unwrap()
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn add_pending_reward(&mut self, account_id: &AccountId, pending_reward: PendingReward) {
+        let mut pending_rewards = self.pending_rewards.get(account_id).unwrap_or_default();
+        pending_rewards.push(pending_reward);
+        self.pending_rewards.insert(account_id, &pending_rewards);
+    }
+}
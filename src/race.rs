@@ -0,0 +1,206 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId, Promise};
+use schemars::JsonSchema;
+use crate::difficulty::Difficulty;
+use crate::structs::race_json::RaceJson;
+use crate::utils::{get_now_ms, BASIS_POINTS};
+use crate::roles::Role;
+use crate::{Game, MazeGameBuyerContract, MazeGameBuyerContractExt, SeedId, DAY_MS};
+
+pub type RaceId = u64;
+
+/// Two players stake the same amount of cheddar and play the same maze. The first win settled takes
+/// the pot minus the house fee, which goes to the treasury
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Race {
+    pub creator: AccountId,
+    pub opponent: Option<AccountId>,
+    pub stake: Balance,
+    // Drawn when the opponent joins, so nobody can solve the maze before staking
+    pub seed_id: Option<SeedId>,
+    pub created_at: u64,
+    pub joined_at: Option<u64>,
+    // Players that started their race game
+    pub started: Vec<AccountId>,
+    // Players whose race game was settled without a win
+    pub lost: Vec<AccountId>,
+    pub winner: Option<AccountId>,
+    // Set once the pot was paid or the stakes refunded
+    pub closed: bool,
+}
+
+impl Race {
+    pub fn is_player(&self, account_id: &AccountId) -> bool {
+        self.creator == *account_id || self.opponent.as_ref() == Some(account_id)
+    }
+}
+
+/// Races that nobody joined, or that nobody won, can be refunded once the timeout passed
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RaceConfig {
+    pub house_fee: u16,
+    pub timeout: u64,
+}
+
+impl RaceConfig {
+    pub fn assert_valid(&self) {
+        require!(self.house_fee <= BASIS_POINTS, format!("House fee can't be more than {}", BASIS_POINTS));
+        require!(self.timeout > 0, "Race timeout must be greater than 0");
+    }
+}
+
+impl Default for RaceConfig {
+    fn default() -> Self {
+        Self {
+            house_fee: 500,
+            timeout: DAY_MS,
+        }
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Starts the caller's game of a race both players joined. Like get_seed_id, it counts against max_concurrent_games
+    #[payable]
+    pub fn start_race_game(&mut self, race_id: RaceId) -> SeedId {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        let mut race = self.races.get(&race_id).expect("Race not found");
+        require!(race.is_player(&account_id), "User is not a player of the race");
+        require!(race.opponent.is_some(), "Race has no opponent yet");
+        require!(!race.closed, "Race is already over");
+        require!(!race.started.contains(&account_id), "Race game already started");
+        // Past the deadline the race can be refunded, so a start would race against refund_race
+        let deadline = self.get_race_deadline(&race);
+        require!(get_now_ms() < deadline, format!("Race timed out at {}", deadline));
+        let seed_id = race.seed_id.expect("Race seed is drawn when the opponent joins");
+
+        let bond = self.assert_min_deposit();
+
+        let storage_usage = env::storage_usage();
+        race.started.push(account_id.clone());
+        self.races.insert(&race_id, &race);
+        self.start_game(&account_id, Game {
+            race_id: Some(race_id),
+            ..Game::new(seed_id, Difficulty::Normal, bond)
+        }, storage_usage);
+        seed_id
+    }
+
+    /// Gives the stakes back when nobody joined, or nobody won, before the timeout. Anyone can call it
+    pub fn refund_race(&mut self, race_id: RaceId) -> Promise {
+        self.assert_settlement_not_paused();
+        let mut race = self.races.get(&race_id).expect("Race not found");
+        require!(!race.closed, "Race is already over");
+        let deadline = self.get_race_deadline(&race);
+        require!(get_now_ms() >= deadline, format!("Race can't be refunded before {}", deadline));
+        self.close_race(race_id, &mut race);
+        self.refund_stakes(&race)
+    }
+
+    pub fn get_race(&self, race_id: RaceId) -> Option<RaceJson> {
+        self.races.get(&race_id).map(|race| RaceJson::from_race(race_id, &race))
+    }
+
+    /// Races waiting for an opponent
+    pub fn get_open_races(&self, from: u64, limit: u64) -> Vec<RaceJson> {
+        self.races.iter()
+            .filter(|(_, race)| race.opponent.is_none() && !race.closed)
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(race_id, race)| RaceJson::from_race(race_id, &race))
+            .collect()
+    }
+
+    pub fn get_race_config(&self) -> RaceConfig {
+        self.race_config.clone()
+    }
+
+    pub fn set_race_config(&mut self, race_config: RaceConfig) {
+        self.assert_role(Role::Admin);
+        race_config.assert_valid();
+        self.race_config = race_config;
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn create_race(&mut self, account_id: AccountId, stake: Balance) -> RaceId {
+        require!(stake > 0, "Stake must be greater than 0");
+        self.last_race_id += 1;
+        self.races.insert(&self.last_race_id, &Race {
+            creator: account_id,
+            opponent: None,
+            stake,
+            seed_id: None,
+            created_at: get_now_ms(),
+            joined_at: None,
+            started: Vec::new(),
+            lost: Vec::new(),
+            winner: None,
+            closed: false,
+        });
+        log!("Race {} created with a stake of {}", self.last_race_id, stake);
+        self.last_race_id
+    }
+
+    /// Returns the part of the transfer above the stake
+    pub(crate) fn join_race(&mut self, account_id: AccountId, race_id: RaceId, amount: Balance) -> Balance {
+        let mut race = self.races.get(&race_id).expect("Race not found");
+        require!(race.opponent.is_none() && !race.closed, "Race is not open");
+        require!(race.creator != account_id, "Can't join your own race");
+        require!(amount >= race.stake, format!("Insufficient cheddar sent {}. Stake is {}", amount, race.stake));
+        race.opponent = Some(account_id);
+        race.joined_at = Some(get_now_ms());
+        race.seed_id = Some(self.next_seed_id());
+        self.races.insert(&race_id, &race);
+        amount - race.stake
+    }
+
+    // The first win takes the pot. When both players lost, they get their stakes back
+    pub(crate) fn record_race_result(&mut self, race_id: RaceId, account_id: &AccountId, won: bool) -> Option<Promise> {
+        let mut race = self.races.get(&race_id).expect("Race not found");
+        if race.closed {
+            log!("Race {} already over. Result not recorded", race_id);
+            return None;
+        }
+        if won {
+            race.winner = Some(account_id.clone());
+            self.close_race(race_id, &mut race);
+            let pot = race.stake * 2;
+            let house_fee = pot * self.race_config.house_fee as u128 / BASIS_POINTS as u128;
+            self.treasury += house_fee;
+            log!("Race {} won by {}. Prize: {}", race_id, account_id, pot - house_fee);
+            return Some(self.pay_cheddar(account_id.clone(), pot - house_fee, format!("Maze race {} prize", race_id)));
+        }
+        race.lost.push(account_id.clone());
+        if race.lost.len() < 2 {
+            self.races.insert(&race_id, &race);
+            return None;
+        }
+        self.close_race(race_id, &mut race);
+        Some(self.refund_stakes(&race))
+    }
+
+    // Games can be started until then, refund_race works from then on
+    fn get_race_deadline(&self, race: &Race) -> u64 {
+        race.joined_at.unwrap_or(race.created_at) + self.race_config.timeout
+    }
+
+    fn close_race(&mut self, race_id: RaceId, race: &mut Race) {
+        race.closed = true;
+        self.races.insert(&race_id, race);
+    }
+
+    fn refund_stakes(&self, race: &Race) -> Promise {
+        let refund = self.pay_cheddar(race.creator.clone(), race.stake, "Maze race refund".to_string());
+        match &race.opponent {
+            Some(opponent) => refund.and(self.pay_cheddar(opponent.clone(), race.stake, "Maze race refund".to_string())),
+            None => refund,
+        }
+    }
+}
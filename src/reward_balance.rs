@@ -0,0 +1,62 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, AccountId, Promise};
+use crate::pending_rewards::{PendingReward, MAX_CLAIMS_PER_CALL};
+use crate::roles::Role;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Rewards credited to the account while accumulate_rewards is on, not minted yet
+    pub fn get_user_claimable_rewards(&self, account_id: AccountId) -> U128 {
+        let balances = self.reward_balances.get(&account_id).unwrap_or_default();
+        U128(balances.iter().map(|balance| balance.amount).sum())
+    }
+
+    /// When accumulate_rewards is on, wins are credited to the player instead of minted one by one
+    pub fn set_reward_claim_mode(&mut self, accumulate_rewards: bool, min_claim_amount: U128) {
+        self.assert_role(Role::Admin);
+        self.accumulate_rewards = accumulate_rewards;
+        self.min_claim_amount = min_claim_amount.0;
+    }
+
+    /// Mints what the caller accumulated. Wins with different referrals are minted separately, so usually
+    /// this is a single mint. Like claim_pending_rewards, at most MAX_CLAIMS_PER_CALL mints are sent per call
+    /// and the rest stays claimable
+    pub fn claim_rewards(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        self.assert_settlement_not_paused();
+        let claimable = self.get_user_claimable_rewards(account_id.clone()).0;
+        assert!(claimable > 0, "No rewards to claim");
+        assert!(claimable >= self.min_claim_amount, "Claimable rewards {} are below the minimum claim of {}", claimable, self.min_claim_amount);
+
+        let mut balances = self.reward_balances.get(&account_id).unwrap_or_default();
+        let claimed: Vec<PendingReward> = balances.drain(..balances.len().min(MAX_CLAIMS_PER_CALL)).collect();
+        if balances.is_empty() {
+            self.reward_balances.remove(&account_id);
+        } else {
+            self.reward_balances.insert(&account_id, &balances);
+        }
+
+        log!("Claiming {} rewards for {}", claimed.iter().map(|balance| balance.amount).sum::<Balance>(), account_id);
+        claimed.into_iter()
+            .map(|balance| self.mint_reward(account_id.clone(), balance.amount, balance.referral))
+            .reduce(|promise, mint| promise.and(mint))
+            .unwrap()
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // Amounts are grouped by referral, so the balance only grows by one entry per referral
+    pub(crate) fn credit_reward(&mut self, account_id: &AccountId, amount: Balance, referral: Option<AccountId>) {
+        let mut balances = self.reward_balances.get(account_id).unwrap_or_default();
+        match balances.iter_mut().find(|balance| balance.referral == referral) {
+            Some(balance) => balance.amount += amount,
+            None => balances.push(PendingReward { amount, referral }),
+        }
+        self.reward_balances.insert(account_id, &balances);
+    }
+}
@@ -0,0 +1,118 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::utils::{get_today_day, to_yocto_u8};
+use crate::roles::Role;
+use crate::timelock::{ConfigChange, ProposalId};
+use crate::{Day, MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// Caps on what end_game can mint, so a leaked owner key can't mint without limit. Raising them is timelocked for
+/// the same reason. Daily challenge rewards are prizes, so only the daily caps apply to them
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardLimits {
+    #[schemars(with = "String")]
+    pub max_per_game: U128,
+    #[schemars(with = "String")]
+    pub max_per_user_daily: U128,
+    #[schemars(with = "String")]
+    pub daily_mint_budget: U128,
+}
+
+impl RewardLimits {
+    /// Each cap has to fit in the next one: a game in the daily limit of a user, and that in the daily budget
+    pub fn assert_valid(&self) {
+        require!(self.max_per_game.0 > 0, "Max reward per game must be greater than 0");
+        require!(self.max_per_game.0 <= self.max_per_user_daily.0, "Max reward per game can't be more than the daily limit of a user");
+        require!(self.max_per_user_daily.0 <= self.daily_mint_budget.0, "Daily limit of a user can't be more than the daily mint budget");
+    }
+
+    /// True when any of the caps is higher than in the current limits
+    pub fn raises(&self, current: &RewardLimits) -> bool {
+        self.max_per_game.0 > current.max_per_game.0
+            || self.max_per_user_daily.0 > current.max_per_user_daily.0
+            || self.daily_mint_budget.0 > current.daily_mint_budget.0
+    }
+}
+
+impl Default for RewardLimits {
+    fn default() -> Self {
+        Self {
+            max_per_game: to_yocto_u8(25),
+            max_per_user_daily: to_yocto_u8(100),
+            daily_mint_budget: U128(to_yocto_u8(100).0 * 100),
+        }
+    }
+}
+
+// Same idea as FreeGameInfo: the amount only counts for the day it was recorded
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct DailyReward {
+    pub day: Day,
+    pub amount: Balance,
+}
+
+impl DailyReward {
+    fn amount_on(&self, day: Day) -> Balance {
+        if self.day == day { self.amount } else { 0 }
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_reward_limits(&self) -> RewardLimits {
+        self.reward_limits.clone()
+    }
+
+    /// Lower limits apply at once. Raising any of them is timelocked, so a leaked key can't lift the caps and
+    /// mint right away. Returns the proposal in that case
+    pub fn set_reward_limits(&mut self, reward_limits: RewardLimits) -> Option<ProposalId> {
+        self.assert_role(Role::Admin);
+        reward_limits.assert_valid();
+        if reward_limits.raises(&self.reward_limits) {
+            return Some(self.propose_config_change(ConfigChange::RewardLimits { reward_limits }));
+        }
+        self.reward_limits = reward_limits;
+        None
+    }
+
+    /// What can still be minted today across all players
+    pub fn get_remaining_mint_budget(&self) -> U128 {
+        let minted = self.daily_minted.amount_on(get_today_day());
+        U128(self.reward_limits.daily_mint_budget.0.saturating_sub(minted))
+    }
+
+    /// What the account can still be rewarded today, taking the global budget into account
+    pub fn get_user_remaining_daily_reward(&self, account_id: AccountId) -> U128 {
+        let rewarded = self.user_daily_rewards.get(&account_id).unwrap_or_default().amount_on(get_today_day());
+        let remaining = self.reward_limits.max_per_user_daily.0.saturating_sub(rewarded);
+        U128(remaining.min(self.get_remaining_mint_budget().0))
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // Panics if the reward goes over any limit. Otherwise counts it against today's limits
+    pub(crate) fn consume_reward_limits(&mut self, account_id: &AccountId, amount: Balance) {
+        require!(amount <= self.reward_limits.max_per_game.0, format!("Reward can't be more than {} per game", self.reward_limits.max_per_game.0));
+        self.consume_daily_reward_limits(account_id, amount);
+    }
+
+    pub(crate) fn consume_daily_reward_limits(&mut self, account_id: &AccountId, amount: Balance) {
+        let day = get_today_day();
+        let user_rewarded = self.user_daily_rewards.get(account_id).unwrap_or_default().amount_on(day);
+        let user_remaining = self.reward_limits.max_per_user_daily.0.saturating_sub(user_rewarded);
+        require!(amount <= user_remaining, format!("Reward exceeds the daily reward limit of the user. Remaining today: {}", user_remaining));
+
+        let minted = self.daily_minted.amount_on(day);
+        let budget_remaining = self.reward_limits.daily_mint_budget.0.saturating_sub(minted);
+        require!(amount <= budget_remaining, format!("Reward exceeds the daily mint budget. Remaining today: {}", budget_remaining));
+
+        self.user_daily_rewards.insert(account_id, &DailyReward { day, amount: user_rewarded + amount });
+        self.daily_minted = DailyReward { day, amount: minted + amount };
+    }
+}
@@ -0,0 +1,71 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, require};
+use schemars::JsonSchema;
+use crate::utils::{to_yocto_u8, BASIS_POINTS};
+use crate::roles::Role;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// What the game server reports when a game ends. The contract turns it into the minted amount
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GameResult {
+    pub won: bool,
+    pub collected_items: u16,
+}
+
+/// How a win is rewarded. The reward goes from max_reward of the difficulty when the maze is solved
+/// instantly down to min_time_share of it when solved right before the time runs out. Every collected
+/// item adds item_reward on top, and the total never goes over max_reward
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardSchedule {
+    pub min_time_share: u16,
+    #[schemars(with = "String")]
+    pub item_reward: U128,
+    pub max_items: u16,
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        Self {
+            min_time_share: 2_000,
+            item_reward: to_yocto_u8(1),
+            max_items: 10,
+        }
+    }
+}
+
+impl RewardSchedule {
+    pub fn assert_valid(&self) {
+        require!(self.min_time_share <= BASIS_POINTS, format!("min_time_share can't be more than {}", BASIS_POINTS));
+    }
+
+    pub fn compute(&self, max_reward: Balance, game_duration: u64, elapsed_ms: u64, collected_items: u16) -> Balance {
+        if elapsed_ms >= game_duration {
+            return 0;
+        }
+        let remaining_ms = (game_duration - elapsed_ms) as u128;
+        let min_share = self.min_time_share as u128;
+        let time_share = min_share + (BASIS_POINTS as u128 - min_share) * remaining_ms / game_duration as u128;
+        let time_reward = max_reward * time_share / BASIS_POINTS as u128;
+        let items_reward = self.item_reward.0 * collected_items.min(self.max_items) as u128;
+        (time_reward + items_reward).min(max_reward)
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_reward_schedule(&self) -> RewardSchedule {
+        self.reward_schedule.clone()
+    }
+
+    pub fn set_reward_schedule(&mut self, reward_schedule: RewardSchedule) {
+        self.assert_role(Role::Admin);
+        reward_schedule.assert_valid();
+        self.reward_schedule = reward_schedule;
+    }
+}
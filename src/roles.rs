@@ -0,0 +1,71 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+/// The owner can do everything. Admins can do everything but change the contracts this one calls
+/// and manage other admins. The rest of the roles only unlock their own methods
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    Admin,
+    // Settles games with end_game
+    GameOperator,
+    // Sets the game costs
+    PricingManager,
+    Pauser,
+    // Manages the ban list and flagged results
+    Moderator,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Only the owner can grant Admin. Admins can grant the other roles
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_can_manage_role(role);
+        self.roles.insert(&(role, account_id));
+    }
+
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_can_manage_role(role);
+        require!(self.roles.remove(&(role, account_id)), "Account doesn't have the role");
+    }
+
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        account_id == self.owner_id
+            || self.roles.contains(&(Role::Admin, account_id.clone()))
+            || self.roles.contains(&(role, account_id))
+    }
+
+    /// Accounts granted the role. The owner isn't listed
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.roles.iter()
+            .filter(|(member_role, _)| *member_role == role)
+            .map(|(_, account_id)| account_id)
+            .collect()
+    }
+
+    pub fn get_account_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.roles.iter()
+            .filter(|(_, member)| *member == account_id)
+            .map(|(role, _)| role)
+            .collect()
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        require!(self.has_role(role, env::predecessor_account_id()), format!("Only the owner or an account with the {:?} role can call this method", role));
+    }
+
+    fn assert_can_manage_role(&self, role: Role) {
+        if role == Role::Admin {
+            self.assert_only_owner();
+        } else {
+            self.assert_role(Role::Admin);
+        }
+    }
+}
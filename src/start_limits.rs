@@ -0,0 +1,122 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::structs::can_start_game_json::CanStartGameJson;
+use crate::utils::get_now_ms;
+use crate::roles::Role;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt, MIN_MS};
+
+const HOUR_MS: u64 = 60 * MIN_MS;
+// A longer cooldown would all but stop accounts from playing, so it is most likely meant in another unit
+pub const MAX_COOLDOWN_SECONDS: u64 = 24 * 3600;
+
+/// Limits on how often an account can call get_seed_id, so seeds can't be fished by restarting games.
+/// 0 disables a limit
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StartLimits {
+    pub cooldown_seconds: u64,
+    pub max_games_per_hour: u16,
+}
+
+impl StartLimits {
+    pub fn assert_valid(&self) {
+        require!(self.cooldown_seconds <= MAX_COOLDOWN_SECONDS, format!("Cooldown can't be more than {} seconds", MAX_COOLDOWN_SECONDS));
+    }
+}
+
+impl Default for StartLimits {
+    fn default() -> Self {
+        Self {
+            cooldown_seconds: 0,
+            max_games_per_hour: 30,
+        }
+    }
+}
+
+// Like DailyReward, the count only holds for the hour it was recorded in
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct GameStarts {
+    pub last_start: u64,
+    pub hour: u64,
+    pub count: u16,
+}
+
+impl GameStarts {
+    fn count_on(&self, hour: u64) -> u16 {
+        if self.hour == hour { self.count } else { 0 }
+    }
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    pub fn get_start_limits(&self) -> StartLimits {
+        self.start_limits.clone()
+    }
+
+    pub fn set_start_limits(&mut self, start_limits: StartLimits) {
+        self.assert_role(Role::Admin);
+        start_limits.assert_valid();
+        self.start_limits = start_limits;
+    }
+
+    /// Whether get_seed_id would let the account start a Normal game now, and if not why and from when
+    pub fn can_start_game(&self, account_id: AccountId) -> CanStartGameJson {
+        if self.pause_flags.game_starts {
+            return CanStartGameJson::blocked("Game starts are paused", None);
+        }
+        if self.is_banned(&account_id) {
+            return CanStartGameJson::blocked("Account is banned", None);
+        }
+        let (remaining_free_games, remaining_paid_games) = self.get_user_remaining_games(&account_id);
+        if remaining_free_games == 0 && remaining_paid_games == 0 {
+            return CanStartGameJson::blocked("No games remaining for the user", None);
+        }
+        let ongoing_games = self.get_ongoing_game_ids(&account_id).into_iter()
+            .filter(|game_id| self.get_ongoing_game(&account_id, *game_id).is_some())
+            .count();
+        if ongoing_games >= self.max_concurrent_games as usize {
+            return CanStartGameJson::blocked("Limit of ongoing games reached", None);
+        }
+        match self.check_start_limits(&account_id) {
+            Err((reason, available_at)) => CanStartGameJson::blocked(&reason, Some(available_at)),
+            Ok(()) => CanStartGameJson {
+                can_start: true,
+                reason: None,
+                available_at: None,
+            },
+        }
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // On failure returns the reason and the time at which the account can start again
+    fn check_start_limits(&self, account_id: &AccountId) -> Result<(), (String, u64)> {
+        let now = get_now_ms();
+        let starts = match self.game_starts.get(account_id) {
+            Some(starts) => starts,
+            None => return Ok(()),
+        };
+        let cooldown_end = starts.last_start + self.start_limits.cooldown_seconds * 1000;
+        if now < cooldown_end {
+            return Err((format!("Game start on cooldown until {}", cooldown_end), cooldown_end));
+        }
+        let hour = now / HOUR_MS;
+        let max_games = self.start_limits.max_games_per_hour;
+        if max_games > 0 && starts.count_on(hour) >= max_games {
+            return Err((format!("Limit of {} games per hour reached", max_games), (hour + 1) * HOUR_MS));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn record_game_start(&mut self, account_id: &AccountId) {
+        self.check_start_limits(account_id).unwrap_or_else(|(reason, _)| env::panic_str(&reason));
+        let now = get_now_ms();
+        let hour = now / HOUR_MS;
+        let count = self.game_starts.get(account_id).unwrap_or_default().count_on(hour) + 1;
+        self.game_starts.insert(account_id, &GameStarts { last_start: now, hour, count });
+    }
+}
@@ -0,0 +1,21 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::achievements::{AchievementId, UnlockedAchievement};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UserAchievementJson {
+	pub achievement_id: AchievementId,
+	pub unlocked_at: u64,
+	pub badge_minted: bool,
+}
+
+impl From<&UnlockedAchievement> for UserAchievementJson {
+    fn from(unlocked: &UnlockedAchievement) -> Self {
+        Self {
+            achievement_id: unlocked.achievement_id.clone(),
+            unlocked_at: unlocked.unlocked_at,
+            badge_minted: unlocked.badge_minted,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use schemars::JsonSchema;
+use crate::ban::Ban;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BanJson {
+	pub account_id: String,
+	pub reason: String,
+	pub banned_by: String,
+	pub banned_at: u64,
+	pub expires_at: Option<u64>,
+}
+
+impl BanJson {
+    pub fn from_ban(account_id: AccountId, ban: &Ban) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            reason: ban.reason.clone(),
+            banned_by: ban.banned_by.to_string(),
+            banned_at: ban.banned_at,
+            expires_at: ban.expires_at,
+        }
+    }
+}
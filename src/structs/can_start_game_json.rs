@@ -0,0 +1,20 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CanStartGameJson {
+	pub can_start: bool,
+	pub reason: Option<String>,
+	pub available_at: Option<u64>,
+}
+
+impl CanStartGameJson {
+    pub fn blocked(reason: &str, available_at: Option<u64>) -> Self {
+        Self {
+            can_start: false,
+            reason: Some(reason.to_string()),
+            available_at,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::daily_challenge::DailyChallenge;
+use crate::{Day, SeedId};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DailyChallengeJson {
+	pub day: Day,
+	pub seed_id: SeedId,
+	pub rewards: Vec<String>,
+	pub players_ranked: u64,
+	pub claimed: u64,
+}
+
+impl DailyChallengeJson {
+    pub fn from_challenge(day: Day, challenge: &DailyChallenge) -> Self {
+        Self {
+            day,
+            seed_id: challenge.seed_id,
+            rewards: challenge.rewards.iter().map(|reward| reward.to_string()).collect(),
+            players_ranked: challenge.ranking.entries.len() as u64,
+            claimed: challenge.claimed.len() as u64,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::difficulty::{Difficulty, DifficultyConfig};
+use crate::GameAmount;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DifficultyConfigJson {
+	pub difficulty: Difficulty,
+	pub cost_multiplier: GameAmount,
+	pub max_game_duration: u64,
+	pub max_reward: String,
+}
+
+impl DifficultyConfigJson {
+    pub fn from_config(difficulty: Difficulty, config: &DifficultyConfig) -> Self {
+        Self {
+            difficulty,
+            cost_multiplier: config.cost_multiplier,
+            max_game_duration: config.max_game_duration,
+            max_reward: config.max_reward.to_string(),
+        }
+    }
+}
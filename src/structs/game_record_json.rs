@@ -0,0 +1,28 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::history::{GameOutcome, GameRecord};
+use crate::SeedId;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GameRecordJson {
+	pub seed_id: SeedId,
+	pub start_time: u64,
+	pub end_time: u64,
+	pub outcome: GameOutcome,
+	pub reward: String,
+	pub referral: Option<String>,
+}
+
+impl From<&GameRecord> for GameRecordJson {
+    fn from(record: &GameRecord) -> Self {
+        Self {
+            seed_id: record.seed_id,
+            start_time: record.start_time,
+            end_time: record.end_time,
+            outcome: record.outcome,
+            reward: record.reward.to_string(),
+            referral: record.referral.as_ref().map(|referral| referral.to_string()),
+        }
+    }
+}
@@ -1,10 +1,38 @@
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use crate::SeedId;
+use crate::{Day, Game, GameId, SeedId};
+use crate::tournament::TournamentId;
+use crate::race::RaceId;
+use crate::difficulty::Difficulty;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct GameJson {
+	pub game_id: GameId,
 	pub seed_id: SeedId,
 	pub start_time: u64,
-}
\ No newline at end of file
+	pub tournament_id: Option<TournamentId>,
+	pub challenge_day: Option<Day>,
+	pub race_id: Option<RaceId>,
+	pub difficulty: Difficulty,
+	pub bond: String,
+	pub extensions: u8,
+	// Time at which the game runs out, extensions included
+	pub deadline: u64,
+}
+impl GameJson {
+    pub fn from_game(game_id: GameId, game: &Game, game_duration: u64) -> Self {
+        Self {
+            game_id,
+            seed_id: game.seed_id,
+            start_time: game.start_time,
+            tournament_id: game.tournament_id,
+            challenge_day: game.challenge_day,
+            race_id: game.race_id,
+            difficulty: game.difficulty,
+            bond: game.bond.to_string(),
+            extensions: game.extensions,
+            deadline: game.start_time + game_duration,
+        }
+    }
+}
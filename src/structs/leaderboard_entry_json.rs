@@ -0,0 +1,26 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::leaderboard::LeaderboardEntry;
+use crate::SeedId;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaderboardEntryJson {
+	pub account_id: String,
+	pub seed_id: SeedId,
+	pub reward: String,
+	pub duration: u64,
+	pub end_time: u64,
+}
+
+impl From<&LeaderboardEntry> for LeaderboardEntryJson {
+    fn from(entry: &LeaderboardEntry) -> Self {
+        Self {
+            account_id: entry.account_id.to_string(),
+            seed_id: entry.seed_id,
+            reward: entry.reward.to_string(),
+            duration: entry.duration,
+            end_time: entry.end_time,
+        }
+    }
+}
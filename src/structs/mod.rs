@@ -1 +1,12 @@
-pub mod game_struct_json;
\ No newline at end of file
+pub mod game_struct_json;
+pub mod game_record_json;
+pub mod leaderboard_entry_json;
+pub mod tournament_json;
+pub mod difficulty_config_json;
+pub mod ban_json;
+pub mod can_start_game_json;
+pub mod daily_challenge_json;
+pub mod race_json;
+pub mod achievement_json;
+pub mod proposal_json;
+pub mod multisig_request_json;
\ No newline at end of file
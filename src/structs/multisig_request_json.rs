@@ -0,0 +1,25 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::multisig::{MultisigAction, MultisigRequest, RequestId};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigRequestJson {
+	pub request_id: RequestId,
+	pub action: MultisigAction,
+	pub created_by: String,
+	pub expires_at: u64,
+	pub confirmations: Vec<String>,
+}
+
+impl MultisigRequestJson {
+    pub fn from_request(request_id: RequestId, request: &MultisigRequest) -> Self {
+        Self {
+            request_id,
+            action: request.action.clone(),
+            created_by: request.created_by.to_string(),
+            expires_at: request.expires_at,
+            confirmations: request.confirmations.iter().map(|approver| approver.to_string()).collect(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::timelock::{ConfigChange, Proposal, ProposalId};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalJson {
+	pub proposal_id: ProposalId,
+	pub change: ConfigChange,
+	pub proposed_by: String,
+	pub proposed_at: u64,
+	pub executable_at: u64,
+}
+
+impl ProposalJson {
+    pub fn from_proposal(proposal_id: ProposalId, proposal: &Proposal) -> Self {
+        Self {
+            proposal_id,
+            change: proposal.change.clone(),
+            proposed_by: proposal.proposed_by.to_string(),
+            proposed_at: proposal.proposed_at,
+            executable_at: proposal.executable_at,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::race::{Race, RaceId};
+use crate::SeedId;
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RaceJson {
+	pub race_id: RaceId,
+	pub creator: String,
+	pub opponent: Option<String>,
+	pub stake: String,
+	// Hidden until the opponent joins
+	pub seed_id: Option<SeedId>,
+	pub created_at: u64,
+	pub joined_at: Option<u64>,
+	pub winner: Option<String>,
+	pub closed: bool,
+}
+
+impl RaceJson {
+    pub fn from_race(race_id: RaceId, race: &Race) -> Self {
+        Self {
+            race_id,
+            creator: race.creator.to_string(),
+            opponent: race.opponent.as_ref().map(|opponent| opponent.to_string()),
+            stake: race.stake.to_string(),
+            seed_id: race.seed_id,
+            created_at: race.created_at,
+            joined_at: race.joined_at,
+            winner: race.winner.as_ref().map(|winner| winner.to_string()),
+            closed: race.closed,
+        }
+    }
+}
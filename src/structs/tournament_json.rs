@@ -0,0 +1,42 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::tournament::{Tournament, TournamentId};
+use crate::{GameAmount, SeedId};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TournamentJson {
+	pub tournament_id: TournamentId,
+	// Hidden until the first game of the tournament is started
+	pub seed_id: Option<SeedId>,
+	pub entry_fee: String,
+	pub start_time: u64,
+	pub end_time: u64,
+	pub payouts: Vec<u16>,
+	pub pool: String,
+	pub players_ranked: u64,
+	pub finalized: bool,
+}
+
+impl TournamentJson {
+    pub fn from_tournament(tournament_id: TournamentId, tournament: &Tournament) -> Self {
+        Self {
+            tournament_id,
+            seed_id: tournament.seed_id,
+            entry_fee: tournament.entry_fee.to_string(),
+            start_time: tournament.start_time,
+            end_time: tournament.end_time,
+            payouts: tournament.payouts.clone(),
+            pool: tournament.pool.to_string(),
+            players_ranked: tournament.ranking.entries.len() as u64,
+            finalized: tournament.finalized,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TournamentPlayerJson {
+	pub remaining_attempts: GameAmount,
+	pub best_duration: Option<u64>,
+}
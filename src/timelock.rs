@@ -0,0 +1,161 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, AccountId};
+use schemars::JsonSchema;
+use crate::config::{assert_valid_game_costs, ConfigPatch, DifficultyLevel};
+use crate::difficulty::DifficultyConfig;
+use crate::extension::ExtensionConfig;
+use crate::reward_limits::RewardLimits;
+use crate::structs::proposal_json::ProposalJson;
+use crate::utils::get_now_ms;
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+pub type ProposalId = u64;
+
+/// Configuration that only changes once timelock_delay has passed since it was proposed
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde", tag = "change", rename_all = "snake_case")]
+pub enum ConfigChange {
+    CheddarContract {
+        #[schemars(with = "String")]
+        account_id: AccountId,
+    },
+    MazeMinterContract {
+        #[schemars(with = "String")]
+        account_id: AccountId,
+    },
+    InsertGameCost {
+        key: u8,
+        #[schemars(with = "String")]
+        value: U128,
+    },
+    RemoveGameCost { key: u8 },
+    // Replaces the whole table, see update_config
+    GameCosts {
+        #[schemars(with = "Vec<(u8, String)>")]
+        costs: Vec<(u8, U128)>,
+    },
+    // Replaces the level with the same difficulty
+    DifficultyLevel { level: DifficultyLevel },
+    ExtensionConfig { extension_config: ExtensionConfig },
+    // Only raising the limits is timelocked, see set_reward_limits
+    RewardLimits { reward_limits: RewardLimits },
+    // A patch that changes pricing or raises the reward limits, applied as a whole. See update_config
+    Config { patch: Box<ConfigPatch> },
+    // The delay itself is timelocked, so it can't be lowered to apply something right away
+    TimelockDelay { delay_seconds: u64 },
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Proposal {
+    pub change: ConfigChange,
+    pub proposed_by: AccountId,
+    pub proposed_at: u64,
+    pub executable_at: u64,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Applies the proposal once its delay passed. Anyone can call it
+    pub fn execute_proposal(&mut self, proposal_id: ProposalId) {
+        let proposal = self.proposals.get(&proposal_id).expect("Proposal not found");
+        require!(get_now_ms() >= proposal.executable_at, format!("Proposal can't be executed before {}", proposal.executable_at));
+        self.proposals.remove(&proposal_id);
+        log!("Executing proposal {}: {:?}", proposal_id, proposal.change);
+        self.apply_config_change(proposal.change);
+    }
+
+    pub fn cancel_proposal(&mut self, proposal_id: ProposalId) {
+        self.assert_only_owner();
+        require!(self.proposals.remove(&proposal_id).is_some(), "Proposal not found");
+    }
+
+    /// Changes waiting for their delay, so players can see what is coming
+    pub fn get_pending_proposals(&self, from: u64, limit: u64) -> Vec<ProposalJson> {
+        self.proposals.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(proposal_id, proposal)| ProposalJson::from_proposal(proposal_id, &proposal))
+            .collect()
+    }
+
+    pub fn get_timelock_delay(&self) -> u64 {
+        self.timelock_delay
+    }
+
+    pub fn set_timelock_delay(&mut self, delay_seconds: u64) -> ProposalId {
+        self.assert_only_owner();
+        self.propose_config_change(ConfigChange::TimelockDelay { delay_seconds })
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    pub(crate) fn propose_config_change(&mut self, change: ConfigChange) -> ProposalId {
+        self.check_config_change(&change);
+        let now = get_now_ms();
+        self.last_proposal_id += 1;
+        log!("Proposal {}: {:?}", self.last_proposal_id, change);
+        self.proposals.insert(&self.last_proposal_id, &Proposal {
+            change,
+            proposed_by: env::predecessor_account_id(),
+            proposed_at: now,
+            executable_at: now + self.timelock_delay,
+        });
+        self.last_proposal_id
+    }
+
+    // Checked when proposed and again when executed, as other proposals may have been applied in between
+    fn check_config_change(&self, change: &ConfigChange) {
+        match change {
+            ConfigChange::InsertGameCost { key, .. } => {
+                assert!(*key > 0, "Key must be greater than 0");
+                assert!(self.game_costs.len() < 4, "Cannot have more than 4 game costs");
+            }
+            ConfigChange::RemoveGameCost { key } => {
+                assert!(self.game_costs.contains_key(key), "Key does not exist");
+            }
+            ConfigChange::GameCosts { costs } => assert_valid_game_costs(costs),
+            ConfigChange::DifficultyLevel { level } => self.check_difficulty_level(level),
+            ConfigChange::ExtensionConfig { extension_config } => extension_config.assert_valid(),
+            ConfigChange::RewardLimits { reward_limits } => reward_limits.assert_valid(),
+            ConfigChange::Config { patch } => self.check_config_patch(patch),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn apply_config_change(&mut self, change: ConfigChange) {
+        self.check_config_change(&change);
+        match change {
+            ConfigChange::CheddarContract { account_id } => self.cheddar_contract = account_id,
+            ConfigChange::MazeMinterContract { account_id } => self.maze_minter_contract = account_id,
+            ConfigChange::InsertGameCost { key, value } => {
+                let mut costs = self.get_game_cost_table();
+                costs.retain(|(current_key, _)| *current_key != key);
+                costs.push((key, value.0));
+                self.replace_game_costs(costs);
+            }
+            ConfigChange::RemoveGameCost { key } => {
+                let mut costs = self.get_game_cost_table();
+                costs.retain(|(current_key, _)| *current_key != key);
+                self.replace_game_costs(costs);
+            }
+            ConfigChange::GameCosts { costs } => {
+                self.replace_game_costs(costs.into_iter().map(|(key, value)| (key, value.0)).collect());
+            }
+            ConfigChange::DifficultyLevel { level } => {
+                self.difficulty_levels.insert(&level.difficulty, &DifficultyConfig {
+                    cost_multiplier: level.cost_multiplier,
+                    max_game_duration: level.game_duration_seconds * 1000,
+                    max_reward: level.max_reward.0,
+                });
+            }
+            ConfigChange::ExtensionConfig { extension_config } => self.extension_config = extension_config,
+            ConfigChange::RewardLimits { reward_limits } => self.reward_limits = reward_limits,
+            ConfigChange::Config { patch } => self.apply_config_patch(*patch),
+            ConfigChange::TimelockDelay { delay_seconds } => self.timelock_delay = delay_seconds * 1000,
+        }
+    }
+}
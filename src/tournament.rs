@@ -0,0 +1,209 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, require, AccountId, Promise, PromiseOrValue};
+use crate::difficulty::Difficulty;
+use crate::leaderboard::{Leaderboard, LeaderboardEntry, LeaderboardKind};
+use crate::structs::leaderboard_entry_json::LeaderboardEntryJson;
+use crate::structs::tournament_json::{TournamentJson, TournamentPlayerJson};
+use crate::utils::{get_now_ms, BASIS_POINTS};
+use crate::roles::Role;
+use crate::{Game, GameAmount, MazeGameBuyerContract, MazeGameBuyerContractExt, SeedId};
+
+pub type TournamentId = u64;
+
+// finalize_tournament sends every prize in one call, at 15 Tgas each with the callback, so more ranks
+// wouldn't fit in the gas of a transaction
+pub const MAX_PAID_RANKS: usize = 10;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Tournament {
+    // Drawn by the first game started, so the maze can't be solved before the tournament starts
+    pub seed_id: Option<SeedId>,
+    pub entry_fee: Balance,
+    pub start_time: u64,
+    pub end_time: u64,
+    // Share of the pool for each rank, best rank first
+    pub payouts: Vec<u16>,
+    pub pool: Balance,
+    // Earliest completion of each player, counted from start_time
+    pub ranking: Leaderboard,
+    pub finalized: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct TournamentPlayer {
+    // One attempt is granted for every entry fee paid
+    pub remaining_attempts: GameAmount,
+    // Counted from the start of the tournament, like the ranking
+    pub best_duration: Option<u64>,
+}
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// All the players of the tournament play the same maze. Returns the id of the new tournament
+    pub fn create_tournament(&mut self, entry_fee: U128, start_time: u64, end_time: u64, payouts: Vec<u16>) -> TournamentId {
+        self.assert_role(Role::Admin);
+        require!(entry_fee.0 > 0, "Entry fee must be greater than 0");
+        require!(start_time < end_time, "Tournament must start before it ends");
+        require!(end_time > get_now_ms(), "Tournament end time is in the past");
+        require!(!payouts.is_empty() && payouts.len() <= MAX_PAID_RANKS, format!("Tournament must pay between 1 and {} ranks", MAX_PAID_RANKS));
+        let payouts_total: u32 = payouts.iter().map(|payout| *payout as u32).sum();
+        require!(payouts_total <= BASIS_POINTS as u32, "Payouts can't add up to more than the pool");
+
+        self.last_tournament_id += 1;
+        self.tournaments.insert(&self.last_tournament_id, &Tournament {
+            seed_id: None,
+            entry_fee: entry_fee.0,
+            start_time,
+            end_time,
+            payouts,
+            pool: 0,
+            ranking: Leaderboard::default(),
+            finalized: false,
+        });
+        self.last_tournament_id
+    }
+
+    /// Starts a game on the tournament maze. Like get_seed_id, it counts against max_concurrent_games
+    #[payable]
+    pub fn start_tournament_game(&mut self, tournament_id: TournamentId) -> SeedId {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_banned(&account_id);
+        let mut tournament = self.tournaments.get(&tournament_id).expect("Tournament not found");
+        let now = get_now_ms();
+        require!(now >= tournament.start_time, "Tournament has not started yet");
+        require!(now < tournament.end_time, "Tournament has already ended");
+
+        let bond = self.assert_min_deposit();
+
+        let storage_usage = env::storage_usage();
+        let key = (tournament_id, account_id.clone());
+        let mut player = self.tournament_players.get(&key).unwrap_or_default();
+        require!(player.remaining_attempts > 0, "No tournament attempts remaining. Pay the entry fee to get one");
+        player.remaining_attempts -= 1;
+        self.tournament_players.insert(&key, &player);
+
+        let seed_id = match tournament.seed_id {
+            Some(seed_id) => seed_id,
+            None => {
+                let seed_id = self.next_seed_id();
+                tournament.seed_id = Some(seed_id);
+                self.tournaments.insert(&tournament_id, &tournament);
+                seed_id
+            }
+        };
+        self.start_game(&account_id, Game {
+            tournament_id: Some(tournament_id),
+            ..Game::new(seed_id, Difficulty::Normal, bond)
+        }, storage_usage);
+        seed_id
+    }
+
+    /// Pays the pool to the best players once the tournament is over. Anyone can call it.
+    /// What the payouts leave of the pool, including the share of ranks nobody reached, goes to the treasury
+    pub fn finalize_tournament(&mut self, tournament_id: TournamentId) -> PromiseOrValue<()> {
+        self.assert_settlement_not_paused();
+        let mut tournament = self.tournaments.get(&tournament_id).expect("Tournament not found");
+        require!(get_now_ms() >= tournament.end_time, "Tournament has not ended yet");
+        require!(!tournament.finalized, "Tournament already finalized");
+        tournament.finalized = true;
+        self.tournaments.insert(&tournament_id, &tournament);
+
+        let mut transfers: Option<Promise> = None;
+        let mut paid: Balance = 0;
+        for (entry, payout) in tournament.ranking.entries.iter().zip(tournament.payouts.iter()) {
+            let prize = tournament.pool * *payout as u128 / BASIS_POINTS as u128;
+            if prize == 0 {
+                continue;
+            }
+            paid += prize;
+            log!("Tournament {} pays {} to {}", tournament_id, prize, entry.account_id);
+            let transfer = self.pay_cheddar(entry.account_id.clone(), prize, format!("Maze tournament {} prize", tournament_id));
+            transfers = Some(match transfers {
+                Some(promise) => promise.and(transfer),
+                None => transfer,
+            });
+        }
+        let leftover = tournament.pool - paid;
+        if leftover > 0 {
+            log!("Tournament {} leaves {} to the treasury", tournament_id, leftover);
+            self.treasury += leftover;
+        }
+        match transfers {
+            Some(promise) => PromiseOrValue::Promise(promise),
+            None => PromiseOrValue::Value(()),
+        }
+    }
+
+    pub fn get_tournament(&self, tournament_id: TournamentId) -> Option<TournamentJson> {
+        self.tournaments.get(&tournament_id).map(|tournament| TournamentJson::from_tournament(tournament_id, &tournament))
+    }
+
+    pub fn get_tournaments(&self, from: u64, limit: u64) -> Vec<TournamentJson> {
+        self.tournaments.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(tournament_id, tournament)| TournamentJson::from_tournament(tournament_id, &tournament))
+            .collect()
+    }
+
+    pub fn get_tournament_ranking(&self, tournament_id: TournamentId, from: u64, limit: u64) -> Vec<LeaderboardEntryJson> {
+        let tournament = self.tournaments.get(&tournament_id).expect("Tournament not found");
+        tournament.ranking.entries.iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(LeaderboardEntryJson::from)
+            .collect()
+    }
+
+    pub fn get_user_tournament(&self, tournament_id: TournamentId, account_id: AccountId) -> TournamentPlayerJson {
+        let player = self.tournament_players.get(&(tournament_id, account_id)).unwrap_or_default();
+        TournamentPlayerJson {
+            remaining_attempts: player.remaining_attempts,
+            best_duration: player.best_duration,
+        }
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    /// Returns the unused part of the transfer
+    pub(crate) fn enter_tournament(&mut self, account_id: AccountId, tournament_id: TournamentId, amount: Balance) -> Balance {
+        let mut tournament = self.tournaments.get(&tournament_id).expect("Tournament not found");
+        require!(get_now_ms() < tournament.end_time, "Tournament has already ended");
+        let attempts = amount / tournament.entry_fee;
+        require!(attempts > 0, format!("Insufficient cheddar sent {}. Entry fee is {}", amount, tournament.entry_fee));
+        let attempts = GameAmount::try_from(attempts).unwrap_or_else(|_| panic!("Too many attempts bought. Limit is {}", GameAmount::MAX));
+
+        let key = (tournament_id, account_id);
+        let mut player = self.tournament_players.get(&key).unwrap_or_default();
+        player.remaining_attempts = player.remaining_attempts.checked_add(attempts).expect("Too many attempts bought");
+        self.tournament_players.insert(&key, &player);
+
+        tournament.pool += attempts as u128 * tournament.entry_fee;
+        self.tournaments.insert(&tournament_id, &tournament);
+        amount % tournament.entry_fee
+    }
+
+    // Only wins settled before the end of the tournament count. The maze is public once the first player
+    // starts, so a later player could solve it before starting. Every player is timed from the start of the
+    // tournament instead of their own game
+    pub(crate) fn record_tournament_result(&mut self, tournament_id: TournamentId, mut entry: LeaderboardEntry) {
+        let mut tournament = self.tournaments.get(&tournament_id).expect("Tournament not found");
+        if entry.end_time >= tournament.end_time {
+            log!("Tournament {} already ended. Result not recorded", tournament_id);
+            return;
+        }
+        entry.duration = entry.end_time - tournament.start_time;
+        let key = (tournament_id, entry.account_id.clone());
+        let mut player = self.tournament_players.get(&key).unwrap_or_default();
+        if player.best_duration.map_or(true, |best| entry.duration < best) {
+            player.best_duration = Some(entry.duration);
+            self.tournament_players.insert(&key, &player);
+        }
+        tournament.ranking.record(LeaderboardKind::CompletionTime, entry);
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+}
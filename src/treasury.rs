@@ -0,0 +1,74 @@
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, near_bindgen, require, AccountId, Gas, NearToken, Promise};
+use crate::{MazeGameBuyerContract, MazeGameBuyerContractExt};
+
+#[near_bindgen]
+impl MazeGameBuyerContract {
+
+    /// Cheddar the contract earned: game and extension sales, race house fees and what is left of the
+    /// tournament pools after their prizes
+    pub fn get_treasury(&self) -> U128 {
+        U128(self.treasury)
+    }
+
+    /// Sends cheddar out of the treasury. A transfer that fails is kept as unpaid cheddar of the receiver.
+    /// With the multisig enabled this goes through a Withdraw request instead
+    pub fn withdraw_treasury(&mut self, receiver_id: AccountId, amount: U128) -> Promise {
+        self.assert_owner_acts_alone();
+        self.internal_withdraw_treasury(receiver_id, amount.0)
+    }
+
+    /// Prizes and refunds whose transfer failed, waiting to be claimed
+    pub fn get_user_unpaid_cheddar(&self, account_id: AccountId) -> U128 {
+        U128(self.unpaid_cheddar.get(&account_id).unwrap_or(0))
+    }
+
+    /// Retries the transfers that failed for the caller. If it fails again, the amount goes back to the ledger
+    pub fn claim_unpaid_cheddar(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.assert_settlement_not_paused();
+        let amount = self.unpaid_cheddar.remove(&account_id).expect("No unpaid cheddar for the user");
+        log!("Claiming {} unpaid cheddar for {}", amount, account_id);
+        self.pay_cheddar(account_id, amount, "Maze unpaid cheddar".to_string())
+    }
+
+    // A failed transfer is kept in the unpaid ledger, so the receiver can claim it later
+    #[private]
+    pub fn on_cheddar_transfer(&mut self, receiver_id: AccountId, amount: U128) -> bool {
+        match env::promise_result(0) {
+            near_sdk::PromiseResult::Successful(_) => true,
+            near_sdk::PromiseResult::Failed => {
+                log!("Transfer of {} to {} failed", amount.0, receiver_id);
+                let unpaid = self.unpaid_cheddar.get(&receiver_id).unwrap_or(0);
+                self.unpaid_cheddar.insert(&receiver_id, &(unpaid + amount.0));
+                false
+            }
+        }
+    }
+}
+
+impl MazeGameBuyerContract {
+
+    // The callback records the amount as unpaid if the transfer fails
+    pub(crate) fn pay_cheddar(&self, receiver_id: AccountId, amount: Balance, memo: String) -> Promise {
+        ext_ft_core::ext(self.cheddar_contract.clone())
+            .with_static_gas(Gas::from_tgas(10))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .ft_transfer(receiver_id.clone(), U128(amount), Some(memo))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(5))
+                    .on_cheddar_transfer(receiver_id, U128(amount))
+            )
+    }
+
+    pub(crate) fn internal_withdraw_treasury(&mut self, receiver_id: AccountId, amount: Balance) -> Promise {
+        require!(amount > 0, "Amount must be greater than 0");
+        require!(amount <= self.treasury, format!("Treasury only holds {}", self.treasury));
+        self.treasury -= amount;
+        log!("Withdrawing {} from the treasury to {}", amount, receiver_id);
+        self.pay_cheddar(receiver_id, amount, "Maze treasury withdrawal".to_string())
+    }
+}
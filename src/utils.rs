@@ -2,6 +2,9 @@ use near_sdk::{env, json_types::U128};
 
 const DAY_MS: u64 = 24 * 3600 * 1000;
 
+// Fees, payouts and shares are all expressed in basis points
+pub const BASIS_POINTS: u16 = 10_000;
+
 pub fn to_yocto_u8(value: u8) -> U128 {
     // Define 10^24 as the multiplier for yoctoNEAR precision
     let multiplier: u128 = 10u128.pow(24);
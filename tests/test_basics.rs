@@ -120,16 +120,24 @@ async fn test_contract_game_costs() -> Result<(), Box<dyn std::error::Error>> {
     }
     assert!(outcome.is_success());
 
+    // Pricing is timelocked, so the cost only changes once the proposal is executed
+    let pending_proposals: serde_json::Value = user_account
+        .view(contract.id(), "get_pending_proposals")
+        .args_json(json!({"from": 0, "limit": 10}))
+        .await?
+        .json()?;
+    assert_eq!(pending_proposals[0]["change"]["change"], "insert_game_cost");
+    assert_eq!(pending_proposals[0]["change"]["value"], value);
+
     let contract_new_game_costs: Vec<[String; 2]> = user_account
         .view(contract.id(), "get_games_costs")
         .args_json(json!({}))
         .await?
         .json()?;
-    // let new_game_costs: vec!([String; 2]) = json!({"1".to_string(): NearToken::from_near(20), "10".to_string(): NearToken::from_near(14)});
     let new_game_costs = vec![
         [
             1.to_string(),
-            NearToken::from_near(20).as_yoctonear().to_string(),
+            NearToken::from_near(15).as_yoctonear().to_string(),
         ],
         [
             10.to_string(),
@@ -158,6 +166,14 @@ async fn test_cheddar_token() -> Result<(), Box<dyn std::error::Error>> {
     }
     assert!(outcome.is_success());
 
+    // The contract address is timelocked, so it only changes once the proposal is executed
+    let pending_proposals: serde_json::Value = user_account
+        .view(contract.id(), "get_pending_proposals")
+        .args_json(json!({"from": 0, "limit": 10}))
+        .await?
+        .json()?;
+    assert_eq!(pending_proposals[0]["change"]["account_id"], new_cheddar_contract.to_string());
+
     let contract_new_cheddar_contract: serde_json::Value = user_account
         .view(contract.id(), "get_cheddar_contract")
         .args_json(json!({}))
@@ -169,7 +185,7 @@ async fn test_cheddar_token() -> Result<(), Box<dyn std::error::Error>> {
     );
     assert_eq!(
         contract_new_cheddar_contract,
-        new_cheddar_contract.to_string()
+        cheddar_token.to_string()
     );
 
     Ok(())